@@ -1,39 +1,262 @@
 // src/audit_report.rs
 
 use serde::{Serialize, Deserialize};
-use std::collections::HashMap;
+use sha2::{Sha256, Digest};
+use std::collections::BTreeMap;
 use chrono::{DateTime, Utc};
-use crate::detector::Span;
+use zeroize::Zeroizing;
+use crate::{AnonymizeError, Result};
+use crate::config::{AuditConfig, MaskConfig};
+use crate::detector::{Category, Confidence, Span};
+use crate::replacement_engine::Masker;
+use crate::policy::Action;
+use crate::conflict_resolver::{Conflict, ConflictRule};
+use crate::scan::Finding;
+use crate::signing::ReportSignature;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct AuditReport {
     pub version: String,
     pub timestamp: DateTime<Utc>,
     pub input_hash: String,
     pub config_hash: String,
+    /// Hash of the anonymized text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_hash: Option<ContentHash>,
+    /// What the records reveal of the original values; reports written
+    /// before disclosure levels existed contain them in full
+    #[serde(default = "Disclosure::legacy")]
+    pub disclosure: Disclosure,
+    /// Session the document was anonymized in, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
     pub statistics: Statistics,
     pub replacements: Vec<ReplacementRecord>,
+    /// Candidates discarded by conflict resolution, with the match they
+    /// lost to
+    #[serde(default)]
+    pub conflicts: Vec<ConflictRecord>,
+    /// Signature over the rest of the report, when a signing key is
+    /// configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<ReportSignature>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Statistics {
     pub total_matches: usize,
-    pub matches_by_category: HashMap<String, usize>,
+    /// Occurrences replaced, by category placeholder name
+    pub matches_by_category: BTreeMap<String, usize>,
+    /// Candidates discarded by conflict resolution, including the parts
+    /// joined by `merge_adjacent`; one per entry of `conflicts`
     pub conflicts_resolved: usize,
+    /// Candidates dropped because their value is allowlisted
+    #[serde(default)]
+    pub suppressed_by_allowlist: usize,
+    /// Candidates dropped by a discard keyword nearby
+    #[serde(default)]
+    pub discarded_by_context: usize,
+    /// Candidates dropped for scoring below their detector's `min_score`
+    #[serde(default)]
+    pub below_threshold: usize,
     pub processing_time_ms: u64,
+    /// Time spent in each stage of the pipeline
+    #[serde(default)]
+    pub timings: StageTimings,
+    /// Candidates, accepted matches and time of each detector, by id
+    #[serde(default)]
+    pub detectors: BTreeMap<String, DetectorStatistics>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Duration of each pipeline stage, in microseconds
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct StageTimings {
+    pub normalization_us: u64,
+    /// All detectors, including mapping spans back to the input
+    pub detection_us: u64,
+    /// Allowlists, denylists, context keywords and score thresholds
+    pub filtering_us: u64,
+    pub conflict_resolution_us: u64,
+    /// Choosing each match's action under the policy
+    pub policy_us: u64,
+    pub replacement_us: u64,
+}
+
+impl StageTimings {
+    /// Stage names and durations, in pipeline order
+    pub fn stages(&self) -> [(&'static str, u64); 6] {
+        [
+            ("normalization", self.normalization_us),
+            ("detection", self.detection_us),
+            ("filtering", self.filtering_us),
+            ("conflict_resolution", self.conflict_resolution_us),
+            ("policy", self.policy_us),
+            ("replacement", self.replacement_us),
+        ]
+    }
+}
+
+/// Activity of one detector during a run
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct DetectorStatistics {
+    /// Matches the detector returned, before any filtering
+    pub candidates: usize,
+    /// Occurrences replaced (or kept) after conflict resolution
+    pub accepted: usize,
+    /// Time spent in the detector's `detect`, in microseconds
+    pub duration_us: u64,
+}
+
+/// A placeholder and every span of the input it replaced
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ReplacementRecord {
-    pub placeholder: String,
-    pub category: String,
+    /// Replacement written to the output; absent for kept values, which
+    /// are only disclosed as `original_value` or `original_hash`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+    pub category: Category,
     pub detector_id: String,
-    pub confidence: String,
-    pub original_span: Span,
+    pub confidence: Confidence,
+    /// Highest score among the occurrences (0.0 to 1.0)
+    #[serde(default)]
+    pub score: f64,
+    pub occurrences: Vec<Span>,
+    /// Original value, in full or masked depending on the disclosure level
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub original_value: Option<String>,
+    /// Salted SHA-256 of the original value, at the `hash` level
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_hash: Option<String>,
+    /// Treatment applied under the category's policy
+    pub action: Action,
+    /// HMAC key id, when the placeholder is a keyed token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+}
+
+/// How much of each original value an audit report reveals
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Disclosure {
+    /// Nothing: records only locate the values
+    #[default]
+    None,
+    /// A salted SHA-256, to check whether a known value was replaced
+    Hash,
+    /// The value masked like the `mask` action, e.g. `ES** **** 1332`
+    Masked,
+    /// The value in clear text; the report then holds all the personal
+    /// data of the document
+    Full,
+}
+
+impl Disclosure {
+    fn legacy() -> Self {
+        Disclosure::Full
+    }
+}
+
+/// Derives the disclosed form of original values for audit records
+pub struct AuditDisclosure {
+    level: Disclosure,
+    salt: Option<Zeroizing<String>>,
+    masker: Masker,
+}
+
+impl Default for AuditDisclosure {
+    fn default() -> Self {
+        Self::new(Disclosure::None)
+    }
+}
+
+impl AuditDisclosure {
+    /// A level needing no secret: `none`, `masked` with the built-in mask
+    /// rules, or `full` (use `hashed` for the `hash` level)
+    pub fn new(level: Disclosure) -> Self {
+        Self {
+            level,
+            salt: None,
+            masker: Masker::default(),
+        }
+    }
+
+    /// The `hash` level, salting every digest with `salt`
+    pub fn hashed(salt: &str) -> Result<Self> {
+        if salt.is_empty() {
+            return Err(AnonymizeError::ConfigError {
+                message: "audit.salt is empty".to_string(),
+            });
+        }
+        Ok(Self {
+            level: Disclosure::Hash,
+            salt: Some(Zeroizing::new(salt.to_string())),
+            masker: Masker::default(),
+        })
+    }
+
+    /// Build the disclosure described by `[audit]`; the `masked` level
+    /// applies the `[replacement.mask]` rules
+    pub fn from_config(config: &AuditConfig, mask: &MaskConfig) -> Result<Self> {
+        match config.disclosure {
+            Disclosure::Hash => {
+                let salt = config.salt.as_ref().ok_or_else(|| AnonymizeError::ConfigError {
+                    message: "audit.salt is required for the hash disclosure level".to_string(),
+                })?;
+                Self::hashed(&salt.resolve("audit.salt")?)
+            }
+            level => Ok(Self {
+                masker: Masker::from_config(mask),
+                ..Self::new(level)
+            }),
+        }
+    }
+
+    pub fn disclosure(&self) -> Disclosure {
+        self.level
+    }
+
+    /// Value recorded as `original_value`
+    pub fn original_value(&self, category: &Category, original: &str) -> Option<String> {
+        match self.level {
+            Disclosure::Masked => Some(self.masker.mask_value(category, original)),
+            Disclosure::Full => Some(original.to_string()),
+            Disclosure::None | Disclosure::Hash => None,
+        }
+    }
+
+    /// Digest recorded as `original_hash`
+    pub fn original_hash(&self, category: &Category, original: &str) -> Option<String> {
+        let salt = self.salt.as_ref().filter(|_| self.level == Disclosure::Hash)?;
+        let mut hasher = Sha256::new();
+        hasher.update(salt.as_bytes());
+        hasher.update(category.placeholder_name().as_bytes());
+        hasher.update([0u8]);
+        hasher.update(original.as_bytes());
+        Some(format!("{:x}", hasher.finalize()))
+    }
+}
+
+/// A discarded candidate, the overlapping match it lost to, and the
+/// rule that decided between them
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConflictRecord {
+    pub discarded: Finding,
+    pub winner: Finding,
+    pub rule: ConflictRule,
+}
+
+impl From<&Conflict> for ConflictRecord {
+    fn from(conflict: &Conflict) -> Self {
+        Self {
+            discarded: Finding::from(&conflict.loser),
+            winner: Finding::from(&conflict.winner),
+            rule: conflict.rule,
+        }
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct ContentHash {
     pub algorithm: String,
     pub value: String,
@@ -0,0 +1,486 @@
+// src/config.rs
+
+use serde::{Serialize, Deserialize};
+use sha2::{Sha256, Digest};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+use crate::{AnonymizeError, Result};
+use crate::normalizer::DEFAULT_MAX_INPUT_SIZE;
+use crate::detector::ChecksumValidator;
+use crate::policy::Action;
+use crate::conflict_resolver::ResolutionStrategy;
+use crate::audit_report::Disclosure;
+
+/// File looked up in the working directory when no path is given
+pub const DEFAULT_CONFIG_FILE: &str = "anonymize.toml";
+
+/// Environment variable that overrides the configuration file path
+pub const CONFIG_PATH_ENV: &str = "ANONYMIZE_CONFIG";
+
+/// Engine configuration, usually loaded from `anonymize.toml`
+///
+/// Every section is optional: an empty file is equivalent to
+/// `AnonymizeConfig::default()`, which enables all built-in detectors
+/// with their own priorities.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnonymizeConfig {
+    pub limits: LimitsConfig,
+    /// Per-detector settings keyed by detector id (`[detectors.phone]`)
+    pub detectors: BTreeMap<String, DetectorConfig>,
+    /// Regex detectors defined in the file (`[[custom_detectors]]`)
+    pub custom_detectors: Vec<CustomDetectorConfig>,
+    /// Term lists by category placeholder name (`[gazetteers.CLIENT_NAME]`)
+    pub gazetteers: BTreeMap<String, GazetteerConfig>,
+    /// Sign-off labels of the `revised_by`, `approved_by` and
+    /// `designed_by` detectors (`[labels]`)
+    pub labels: LabelsConfig,
+    /// Numbering templates of the `document_number` detector
+    /// (`[[document_numbers]]`); a built-in template applies when none is set
+    pub document_numbers: Vec<DocumentTemplateConfig>,
+    pub context: ContextConfig,
+    /// Values never or always anonymized (`[lists]`)
+    pub lists: ValueListsConfig,
+    pub conflicts: ConflictsConfig,
+    pub policy: PolicyConfig,
+    pub replacement: ReplacementConfig,
+    pub audit: AuditConfig,
+    pub ledger: LedgerConfig,
+}
+
+/// Size limits applied to incoming content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig {
+    /// Maximum text size accepted by the engine, in bytes
+    pub max_input_size: usize,
+    /// Maximum uploaded file size accepted by the web server, in bytes
+    pub max_file_size: usize,
+    /// Maximum number of sessions open at once on the web server
+    pub max_sessions: usize,
+    /// Seconds after which an unused web server session is closed
+    pub session_idle_timeout: u64,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_input_size: DEFAULT_MAX_INPUT_SIZE,
+            max_file_size: 10 * 1024 * 1024,
+            max_sessions: 1000,
+            session_idle_timeout: 24 * 60 * 60,
+        }
+    }
+}
+
+/// Settings for a single detector
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DetectorConfig {
+    pub enabled: bool,
+    /// Overrides the detector's built-in priority
+    pub priority: Option<u32>,
+    /// Candidates scoring below this value (0.0 to 1.0) are dropped
+    pub min_score: Option<f64>,
+}
+
+impl Default for DetectorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            priority: None,
+            min_score: None,
+        }
+    }
+}
+
+/// A regex detector defined in configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomDetectorConfig {
+    /// Detector id, also usable in `[detectors.<id>]`
+    pub id: String,
+    pub pattern: String,
+    /// Category name; matches are reported as `Category::Custom`
+    /// and replaced with `[<CATEGORY>_001]`
+    pub category: String,
+    #[serde(default = "default_custom_priority")]
+    pub priority: u32,
+    /// Score of matches (0.0 to 1.0); validated matches score 0.95
+    #[serde(default = "default_custom_score")]
+    pub score: f64,
+    /// Checksum matches must pass (`luhn`, `mod97`, `spanish_id`)
+    #[serde(default)]
+    pub validator: Option<ChecksumValidator>,
+}
+
+fn default_custom_priority() -> u32 {
+    50
+}
+
+fn default_custom_score() -> f64 {
+    crate::detector::DEFAULT_CUSTOM_SCORE
+}
+
+/// Term list of a gazetteer detector
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GazetteerConfig {
+    /// Files with one term per line (`#` starts a comment line)
+    pub files: Vec<String>,
+    /// Terms listed inline
+    pub terms: Vec<String>,
+}
+
+/// Field labels introducing sign-off names, matched ignoring case and
+/// accents; each list replaces the built-in one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LabelsConfig {
+    pub revised_by: Vec<String>,
+    pub approved_by: Vec<String>,
+    pub designed_by: Vec<String>,
+    /// Capitalized words that end a name, e.g. the next field of a title
+    /// block (`Fecha`, `Date`)
+    pub stop_words: Vec<String>,
+    /// Words captured after a label at most
+    pub max_words: usize,
+}
+
+impl Default for LabelsConfig {
+    fn default() -> Self {
+        let list = |labels: &[&str]| labels.iter().map(|l| l.to_string()).collect();
+        Self {
+            revised_by: list(&[
+                "Revisado por", "Revisó", "Reviewed by", "Checked by", "Revised by",
+                "Vérifié par", "Révisé par", "Geprüft von", "Verificado por",
+            ]),
+            approved_by: list(&[
+                "Aprobado por", "Aprobó", "Approved by", "Approuvé par",
+                "Freigegeben von", "Genehmigt von", "Aprovado por",
+            ]),
+            designed_by: list(&[
+                "Diseñado por", "Dibujado por", "Elaborado por", "Designed by", "Drawn by",
+                "Prepared by", "Conçu par", "Dessiné par", "Erstellt von", "Gezeichnet von",
+            ]),
+            stop_words: list(&[
+                "Fecha", "Date", "Datum", "Firma", "Signature", "Unterschrift", "Rev", "Revisión",
+                "Revision", "Hoja", "Sheet", "Blatt", "Escala", "Scale",
+            ]),
+            max_words: 4,
+        }
+    }
+}
+
+/// A document numbering template, e.g. `A{3}-9{3}-(ME|EL)-(DWG|SPC)-9{4}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DocumentTemplateConfig {
+    pub template: String,
+    /// Accept a revision suffix such as ` Rev. B`
+    #[serde(default)]
+    pub revision: bool,
+}
+
+/// Context analysis around matches (`[context]`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ContextConfig {
+    pub enabled: bool,
+    /// Words inspected on each side of a match
+    pub window: usize,
+    /// Score added when a boost keyword is nearby
+    pub boost: f64,
+    /// Score removed when a suppress keyword is nearby
+    pub penalty: f64,
+    /// Keywords by category placeholder name (`[context.categories.PHONE]`);
+    /// each entry replaces the built-in lists of its category
+    pub categories: BTreeMap<String, ContextKeywords>,
+}
+
+impl Default for ContextConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window: 3,
+            boost: 0.2,
+            penalty: 0.3,
+            categories: BTreeMap::new(),
+        }
+    }
+}
+
+/// Keywords of one category, matched ignoring case and accents
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ContextKeywords {
+    /// Raise the score (`Tel.`, `IBAN:`)
+    pub boost: Vec<String>,
+    /// Lower the score (`cantidad`, `qty`)
+    pub suppress: Vec<String>,
+    /// Drop the match
+    pub discard: Vec<String>,
+}
+
+/// Allowlists and denylists of values
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ValueListsConfig {
+    /// Lists by category placeholder name (`[lists.categories.PHONE]`)
+    pub categories: BTreeMap<String, ValueListEntries>,
+    /// Lists by detector id (`[lists.detectors.email]`)
+    pub detectors: BTreeMap<String, ValueListEntries>,
+}
+
+/// Values of one category or detector
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ValueListEntries {
+    /// Never anonymized
+    pub allow: Vec<String>,
+    /// Always anonymized, bypassing context discards and `min_score`
+    pub deny: Vec<String>,
+}
+
+/// Action applied to each category (`[policy]`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PolicyConfig {
+    /// Action for categories not listed in `categories`
+    pub default: Action,
+    /// Actions by category placeholder name (`IBAN = "mask"`)
+    pub categories: BTreeMap<String, Action>,
+}
+
+/// How overlapping matches are resolved (`[conflicts]`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConflictsConfig {
+    pub strategy: ResolutionStrategy,
+}
+
+/// Settings of the replacement actions (`[replacement]`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReplacementConfig {
+    pub hmac: HmacConfig,
+    pub synthetic: SyntheticConfig,
+    pub mask: MaskConfig,
+}
+
+/// Key ring for the `hash` action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HmacConfig {
+    /// Id of the key used to generate tokens
+    pub active_key: Option<String>,
+    /// Hex characters kept from the HMAC
+    pub token_length: usize,
+    /// Keys by id; retired keys may be kept after a rotation
+    pub keys: BTreeMap<String, SecretSource>,
+}
+
+impl Default for HmacConfig {
+    fn default() -> Self {
+        Self {
+            active_key: None,
+            token_length: crate::replacement_engine::DEFAULT_TOKEN_LENGTH,
+            keys: BTreeMap::new(),
+        }
+    }
+}
+
+/// What audit reports reveal of the original values (`[audit]`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuditConfig {
+    pub disclosure: Disclosure,
+    /// Salt of the `hash` disclosure level
+    pub salt: Option<SecretSource>,
+    pub signing: SigningConfig,
+}
+
+/// Hash-chained record of every run (`[ledger]`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LedgerConfig {
+    /// JSON-lines file appended to; no ledger is kept when unset
+    pub path: Option<PathBuf>,
+}
+
+/// Key ring signing audit reports (`[audit.signing]`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SigningConfig {
+    /// Id of the key signing new reports; reports are unsigned when unset
+    pub active_key: Option<String>,
+    /// Keys by id; retired keys may be kept to verify older reports
+    pub keys: BTreeMap<String, SecretSource>,
+}
+
+/// Seed for the `synthesize` action
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SyntheticConfig {
+    /// Secret seed; the same seed reproduces the same fake values
+    pub seed: SecretSource,
+}
+
+/// Settings for the `mask` action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MaskConfig {
+    pub mask_char: char,
+    /// Rules by category placeholder name (`[replacement.mask.categories.IBAN]`)
+    pub categories: BTreeMap<String, MaskRule>,
+}
+
+impl Default for MaskConfig {
+    fn default() -> Self {
+        Self {
+            mask_char: '*',
+            categories: BTreeMap::new(),
+        }
+    }
+}
+
+/// Characters left visible when masking a category
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MaskRule {
+    /// Leading letters/digits to keep (separators are not counted)
+    pub keep_first: usize,
+    /// Trailing letters/digits to keep (separators are not counted)
+    pub keep_last: usize,
+    /// Keep everything from the `@` of an e-mail address
+    pub keep_domain: bool,
+}
+
+/// A secret given inline or read from an environment variable.
+///
+/// Inline values are never serialized, so they do not leak into the
+/// configuration hash; rotate the key id along with the secret.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SecretSource {
+    #[serde(skip_serializing)]
+    pub value: Option<String>,
+    /// Name of the environment variable holding the secret
+    pub env: Option<String>,
+}
+
+impl std::fmt::Debug for SecretSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecretSource")
+            .field("value", &self.value.as_ref().map(|_| "<redacted>"))
+            .field("env", &self.env)
+            .finish()
+    }
+}
+
+impl SecretSource {
+    /// Read the secret; `name` identifies it in error messages
+    pub fn resolve(&self, name: &str) -> Result<Zeroizing<String>> {
+        match (&self.value, &self.env) {
+            (Some(value), None) => Ok(Zeroizing::new(value.clone())),
+            (None, Some(var)) => std::env::var(var)
+                .map(Zeroizing::new)
+                .map_err(|_| AnonymizeError::ConfigError {
+                    message: format!("Environment variable '{}' for {} is not set", var, name),
+                }),
+            _ => Err(AnonymizeError::ConfigError {
+                message: format!("{} must set exactly one of 'value' or 'env'", name),
+            }),
+        }
+    }
+}
+
+impl AnonymizeConfig {
+    /// Parse a configuration from TOML source
+    pub fn from_toml_str(source: &str) -> Result<Self> {
+        toml::from_str(source).map_err(|e| AnonymizeError::ConfigError {
+            message: format!("Invalid configuration: {}", e),
+        })
+    }
+
+    /// Read and parse a configuration file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let source = std::fs::read_to_string(path)?;
+        Self::from_toml_str(&source)
+    }
+
+    /// Load the configuration used by the server and CLI.
+    ///
+    /// Resolution order: the file named by `ANONYMIZE_CONFIG`, then
+    /// `anonymize.toml` in the working directory, then the defaults.
+    pub fn load() -> Result<Self> {
+        if let Ok(path) = std::env::var(CONFIG_PATH_ENV) {
+            return Self::from_file(path);
+        }
+        if Path::new(DEFAULT_CONFIG_FILE).exists() {
+            return Self::from_file(DEFAULT_CONFIG_FILE);
+        }
+        Ok(Self::default())
+    }
+
+    /// Settings for a detector, falling back to the defaults when absent
+    pub fn detector(&self, id: &str) -> DetectorConfig {
+        self.detectors.get(id).cloned().unwrap_or_default()
+    }
+
+    /// SHA-256 of the canonical JSON serialization of the configuration,
+    /// followed by the SHA-256 of each gazetteer term file.
+    ///
+    /// Maps are ordered, so the hash does not depend on the order in which
+    /// sections appear in the TOML file. Editing a term list changes the
+    /// hash even when its path stays the same; an unreadable file is left
+    /// out, since loading the gazetteer reports it.
+    pub fn hash(&self) -> String {
+        let canonical = serde_json::to_vec(self)
+            .expect("BUG: configuration is always serializable");
+        let mut hasher = Sha256::new();
+        hasher.update(&canonical);
+        for file in self.gazetteers.values().flat_map(|g| &g.files) {
+            if let Ok(content) = std::fs::read(file) {
+                hasher.update(Sha256::digest(&content));
+            }
+        }
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_covers_term_file_contents() {
+        let path = std::env::temp_dir().join(format!("anonymize-terms-{}.txt", std::process::id()));
+        std::fs::write(&path, "Acme\n").unwrap();
+        let mut config = AnonymizeConfig::default();
+        config.gazetteers.insert("CLIENT_NAME".to_string(), GazetteerConfig {
+            files: vec![path.display().to_string()],
+            terms: Vec::new(),
+        });
+
+        let before = config.hash();
+        assert_eq!(config.hash(), before);
+        std::fs::write(&path, "Acme\nGlobex\n").unwrap();
+        let after = config.hash();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_ne!(before, after);
+    }
+
+    #[test]
+    fn hash_ignores_section_order() {
+        let a: AnonymizeConfig = toml::from_str(
+            "[gazetteers.CLIENT_NAME]\nterms = [\"Acme\"]\n[gazetteers.PROJECT_NAME]\nterms = [\"Atlas\"]\n",
+        ).unwrap();
+        let b: AnonymizeConfig = toml::from_str(
+            "[gazetteers.PROJECT_NAME]\nterms = [\"Atlas\"]\n[gazetteers.CLIENT_NAME]\nterms = [\"Acme\"]\n",
+        ).unwrap();
+        assert_eq!(a.hash(), b.hash());
+    }
+}
@@ -1,16 +1,149 @@
 // src/conflict_resolver.rs
 
-use crate::detector::CandidateMatch;
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use crate::detector::{CandidateMatch, Confidence, Span};
 
-/// Resolves overlapping matches deterministically.
-pub struct ConflictResolver;
+/// Resolves overlapping matches with a `ConflictStrategy`,
+/// leftmost-longest unless another one is chosen
+pub struct ConflictResolver {
+    strategy: Box<dyn ConflictStrategy>,
+}
+
+/// Decides which of a set of overlapping candidates are kept.
+///
+/// Candidates are located in `text`, the original input. Custom
+/// strategies can be installed with `Anonymizer::set_conflict_strategy`.
+pub trait ConflictStrategy: Send + Sync {
+    /// Split candidates into non-overlapping accepted matches, in text
+    /// order, and the candidates they discarded
+    fn resolve(&self, text: &str, candidates: Vec<CandidateMatch>) -> Resolution;
+}
+
+/// Built-in strategies, selected by `[conflicts] strategy`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResolutionStrategy {
+    /// Earliest start, then longest, then highest priority
+    #[default]
+    LeftmostLongest,
+    /// Keep the set of non-overlapping matches with the highest total
+    /// priority (weighted interval scheduling)
+    PriorityFirst,
+    /// Keep the highest-scoring matches first
+    ConfidenceFirst,
+    /// Resolve leftmost-longest, then join adjacent matches of the same
+    /// category
+    MergeAdjacent,
+}
+
+impl ResolutionStrategy {
+    pub fn build(&self) -> Box<dyn ConflictStrategy> {
+        match self {
+            ResolutionStrategy::LeftmostLongest => Box::new(LeftmostLongest),
+            ResolutionStrategy::PriorityFirst => Box::new(PriorityFirst),
+            ResolutionStrategy::ConfidenceFirst => Box::new(ConfidenceFirst),
+            ResolutionStrategy::MergeAdjacent => Box::new(MergeAdjacent),
+        }
+    }
+}
+
+/// Outcome of conflict resolution
+#[derive(Debug, Clone, Default)]
+pub struct Resolution {
+    /// Non-overlapping matches, in text order
+    pub accepted: Vec<CandidateMatch>,
+    /// Candidates that overlapped an accepted match
+    pub discarded: Vec<Conflict>,
+}
+
+/// A candidate that lost to an overlapping match
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub loser: CandidateMatch,
+    pub winner: CandidateMatch,
+    pub rule: ConflictRule,
+}
+
+/// Criterion that decided a conflict, in the order they are applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictRule {
+    /// The winner starts earlier
+    Position,
+    /// Same start, the winner is longer
+    Length,
+    /// Same span, the winner's detector has a higher priority
+    Priority,
+    /// The winner has a higher score
+    Score,
+    /// The matches kept in the loser's place have a higher total priority
+    /// than the loser, although each may be lower
+    Combined,
+    /// Joined with an adjacent match of the same category
+    Merged,
+    /// Tied on every other criterion, the winner's detector id sorts first
+    DetectorId,
+}
+
+impl ConflictRule {
+    /// First criterion of the resolution order that tells two
+    /// overlapping candidates apart
+    pub fn between(winner: &CandidateMatch, loser: &CandidateMatch) -> Self {
+        Self::first_difference(
+            &[ConflictRule::Position, ConflictRule::Length, ConflictRule::Priority],
+            winner,
+            loser,
+        )
+    }
+
+    /// First of `order` on which two candidates differ
+    fn first_difference(order: &[ConflictRule], winner: &CandidateMatch, loser: &CandidateMatch) -> Self {
+        order.iter()
+            .copied()
+            .find(|rule| match rule {
+                ConflictRule::Position => winner.span.start != loser.span.start,
+                ConflictRule::Length => length(winner) != length(loser),
+                ConflictRule::Priority => winner.priority != loser.priority,
+                ConflictRule::Score => winner.score != loser.score,
+                _ => false,
+            })
+            .unwrap_or(ConflictRule::DetectorId)
+    }
+}
+
+fn length(m: &CandidateMatch) -> usize {
+    m.span.end - m.span.start
+}
 
 impl ConflictResolver {
-    /// Resolve conflicts in a list of candidate matches.
-    /// Algorithm:
-    /// 1. Sort all candidates by (start_position ASC, length DESC, priority DESC)
-    /// 2. Iterate and accept if it doesn't overlap with the last accepted match.
-    pub fn resolve(mut candidates: Vec<CandidateMatch>) -> Vec<CandidateMatch> {
+    pub fn new(strategy: Box<dyn ConflictStrategy>) -> Self {
+        Self { strategy }
+    }
+
+    /// Split candidates located in `text` into accepted matches and the
+    /// candidates they discarded
+    pub fn resolve(&self, text: &str, candidates: Vec<CandidateMatch>) -> Resolution {
+        self.strategy.resolve(text, candidates)
+    }
+}
+
+impl Default for ConflictResolver {
+    fn default() -> Self {
+        Self::new(Box::new(LeftmostLongest))
+    }
+}
+
+/// The default strategy: keeps the match that starts first, then the
+/// longest, then the one of highest priority.
+///
+/// Candidates are sorted by (start ASC, length DESC, priority DESC,
+/// detector id ASC), and each is accepted unless it overlaps the last
+/// accepted match.
+pub struct LeftmostLongest;
+
+impl ConflictStrategy for LeftmostLongest {
+    fn resolve(&self, _text: &str, mut candidates: Vec<CandidateMatch>) -> Resolution {
         candidates.sort_by(|a, b| {
             // 1. Sort by start position ASC
             a.span.start.cmp(&b.span.start)
@@ -26,16 +159,381 @@ impl ConflictResolver {
                 .then_with(|| a.detector_id.cmp(&b.detector_id))
         });
 
-        let mut accepted = Vec::new();
+        let mut resolution = Resolution::default();
         let mut last_end = 0;
 
         for candidate in candidates {
             if candidate.span.start >= last_end {
                 last_end = candidate.span.end;
-                accepted.push(candidate);
+                resolution.accepted.push(candidate);
+            } else {
+                // Overlaps the last accepted match, which won on sort order
+                let winner = resolution.accepted.last()
+                    .expect("BUG: an overlap implies an accepted match")
+                    .clone();
+                resolution.discarded.push(Conflict {
+                    rule: ConflictRule::between(&winner, &candidate),
+                    loser: candidate,
+                    winner,
+                });
             }
         }
 
-        accepted
+        resolution
+    }
+}
+
+/// Keeps the non-overlapping subset with the highest total priority,
+/// breaking ties by covered length, then by total score.
+///
+/// A long low-priority match no longer hides a checksummed value that
+/// starts one character later; several matches can, however, together
+/// outweigh a single match they overlap.
+pub struct PriorityFirst;
+
+/// Lexicographic weight: total priority, covered bytes, total score in
+/// thousandths
+type Weight = (u64, u64, u64);
+
+fn weight(m: &CandidateMatch) -> Weight {
+    (m.priority as u64, length(m) as u64, (m.score * 1000.0).round() as u64)
+}
+
+fn add(a: Weight, b: Weight) -> Weight {
+    (a.0 + b.0, a.1 + b.1, a.2 + b.2)
+}
+
+impl ConflictStrategy for PriorityFirst {
+    fn resolve(&self, _text: &str, mut candidates: Vec<CandidateMatch>) -> Resolution {
+        candidates.sort_by(|a, b| {
+            a.span.end.cmp(&b.span.end)
+                .then_with(|| a.span.start.cmp(&b.span.start))
+                .then_with(|| b.priority.cmp(&a.priority))
+                .then_with(|| a.detector_id.cmp(&b.detector_id))
+        });
+
+        // best[i]: heaviest compatible subset of the first i candidates
+        let mut best: Vec<Weight> = vec![(0, 0, 0)];
+        let mut previous = Vec::with_capacity(candidates.len());
+        for (i, candidate) in candidates.iter().enumerate() {
+            let p = candidates[..i].partition_point(|c| c.span.end <= candidate.span.start);
+            previous.push(p);
+            best.push(best[i].max(add(best[p], weight(candidate))));
+        }
+
+        let mut taken = vec![false; candidates.len()];
+        let mut i = candidates.len();
+        while i > 0 {
+            let candidate = &candidates[i - 1];
+            if add(best[previous[i - 1]], weight(candidate)) > best[i - 1] {
+                taken[i - 1] = true;
+                i = previous[i - 1];
+            } else {
+                i -= 1;
+            }
+        }
+
+        let (accepted, losers): (Vec<_>, Vec<_>) = candidates
+            .into_iter()
+            .zip(taken)
+            .partition(|(_, taken)| *taken);
+        let mut accepted: Vec<CandidateMatch> = accepted.into_iter().map(|(m, _)| m).collect();
+        accepted.sort_by_key(|m| m.span.start);
+
+        let order = [ConflictRule::Priority, ConflictRule::Length, ConflictRule::Score, ConflictRule::Position];
+        let discarded = losers
+            .into_iter()
+            .map(|(loser, _)| {
+                let winner = overlapping(&accepted, loser.span)
+                    .max_by_key(|m| m.priority)
+                    .expect("BUG: a discarded candidate overlaps an accepted match")
+                    .clone();
+                let rule = if weight(&loser) > weight(&winner) {
+                    ConflictRule::Combined
+                } else {
+                    ConflictRule::first_difference(&order, &winner, &loser)
+                };
+                Conflict { loser, winner, rule }
+            })
+            .collect();
+
+        Resolution { accepted, discarded }
+    }
+}
+
+/// Accepted matches (sorted, non-overlapping) overlapping a span
+fn overlapping(accepted: &[CandidateMatch], span: Span) -> impl Iterator<Item = &CandidateMatch> {
+    let first = accepted.partition_point(|m| m.span.end <= span.start);
+    accepted[first..].iter().take_while(move |m| m.span.start < span.end)
+}
+
+/// Keeps the highest-scoring matches first, then the highest priority,
+/// then the longest; a value confirmed by a checksum beats any pattern
+/// that merely looks plausible.
+pub struct ConfidenceFirst;
+
+impl ConflictStrategy for ConfidenceFirst {
+    fn resolve(&self, _text: &str, mut candidates: Vec<CandidateMatch>) -> Resolution {
+        candidates.sort_by(|a, b| {
+            b.score.total_cmp(&a.score)
+                .then_with(|| b.priority.cmp(&a.priority))
+                .then_with(|| length(b).cmp(&length(a)))
+                .then_with(|| a.span.start.cmp(&b.span.start))
+                .then_with(|| a.detector_id.cmp(&b.detector_id))
+        });
+
+        let order = [ConflictRule::Score, ConflictRule::Priority, ConflictRule::Length, ConflictRule::Position];
+        // Accepted matches by start; they never overlap, so the last one
+        // starting before a candidate ends is the only one to check
+        let mut accepted: BTreeMap<usize, CandidateMatch> = BTreeMap::new();
+        let mut discarded = Vec::new();
+        for candidate in candidates {
+            let winner = accepted
+                .range(..candidate.span.end)
+                .next_back()
+                .map(|(_, m)| m)
+                .filter(|m| m.span.end > candidate.span.start);
+            match winner {
+                Some(winner) => discarded.push(Conflict {
+                    rule: ConflictRule::first_difference(&order, winner, &candidate),
+                    winner: winner.clone(),
+                    loser: candidate,
+                }),
+                None => {
+                    accepted.insert(candidate.span.start, candidate);
+                }
+            }
+        }
+
+        Resolution {
+            accepted: accepted.into_values().collect(),
+            discarded,
+        }
+    }
+}
+
+/// Resolves overlaps leftmost-longest, then joins consecutive matches of
+/// the same category separated only by spaces and tabs on one line, e.g.
+/// a first name and a surname found separately. Matches on different
+/// lines usually belong to different fields and stay apart.
+///
+/// The joined match takes the highest priority and score of its parts,
+/// and is `Verified` only if every part is; its parts are reported as
+/// discarded by the `merged` rule.
+pub struct MergeAdjacent;
+
+impl ConflictStrategy for MergeAdjacent {
+    fn resolve(&self, text: &str, candidates: Vec<CandidateMatch>) -> Resolution {
+        let resolution = LeftmostLongest.resolve(text, candidates);
+
+        let mut groups: Vec<Vec<CandidateMatch>> = Vec::new();
+        for candidate in resolution.accepted {
+            let joins = groups.last().and_then(|group| group.last()).is_some_and(|last| {
+                last.category == candidate.category
+                    && text.get(last.span.end..candidate.span.start).is_some_and(|gap| gap.chars().all(|c| c == ' ' || c == '\t'))
+            });
+            match groups.last_mut() {
+                Some(group) if joins => group.push(candidate),
+                _ => groups.push(vec![candidate]),
+            }
+        }
+
+        let mut accepted = Vec::with_capacity(groups.len());
+        let mut discarded = resolution.discarded;
+        for group in groups {
+            if group.len() == 1 {
+                accepted.extend(group);
+                continue;
+            }
+            let joined = join(text, &group);
+            discarded.extend(group.into_iter().map(|loser| Conflict {
+                loser,
+                winner: joined.clone(),
+                rule: ConflictRule::Merged,
+            }));
+            accepted.push(joined);
+        }
+
+        Resolution { accepted, discarded }
+    }
+}
+
+/// Single match spanning a group of same-category matches
+fn join(text: &str, group: &[CandidateMatch]) -> CandidateMatch {
+    let first = &group[0];
+    let span = Span {
+        start: first.span.start,
+        end: group[group.len() - 1].span.end,
+    };
+    let raw_value = text.get(span.start..span.end).unwrap_or_default().to_string();
+    let confidence = if group.iter().all(|m| m.confidence == Confidence::Verified) {
+        Confidence::Verified
+    } else {
+        Confidence::PatternOnly
+    };
+
+    CandidateMatch {
+        span,
+        detector_id: first.detector_id.clone(),
+        category: first.category.clone(),
+        priority: group.iter().map(|m| m.priority).max().unwrap_or(first.priority),
+        confidence,
+        score: group.iter().map(|m| m.score).fold(0.0, f64::max),
+        normalized_value: Some(raw_value.split_whitespace().collect::<Vec<_>>().join(" ")),
+        raw_value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detector::Category;
+
+    const TEXT: &str = "Pay ES91 2100 0418 4502 0005 1332 to Ana Ruiz\n\nRuiz Pérez, tel 612 345 678";
+
+    /// Candidate for the first occurrence of `value` in `TEXT`
+    fn candidate(value: &str, id: &str, category: Category, priority: u32, score: f64) -> CandidateMatch {
+        let start = TEXT.find(value).unwrap();
+        CandidateMatch {
+            span: Span { start, end: start + value.len() },
+            detector_id: id.to_string(),
+            category,
+            priority,
+            confidence: if score >= 0.9 { Confidence::Verified } else { Confidence::PatternOnly },
+            score,
+            raw_value: value.to_string(),
+            normalized_value: None,
+        }
+    }
+
+    fn values(resolution: &Resolution) -> Vec<&str> {
+        resolution.accepted.iter().map(|m| m.raw_value.as_str()).collect()
+    }
+
+    fn rules(resolution: &Resolution) -> Vec<(&str, &str, ConflictRule)> {
+        resolution.discarded.iter()
+            .map(|c| (c.loser.detector_id.as_str(), c.winner.detector_id.as_str(), c.rule))
+            .collect()
+    }
+
+    #[test]
+    fn leftmost_longest_records_each_discarded_overlap() {
+        let candidates = vec![
+            candidate("2100 0418", "serial", Category::SerialNumber, 40, 0.5),
+            candidate("ES91 2100 0418 4502 0005 1332", "iban", Category::Iban, 100, 0.99),
+            candidate("ES91 2100 0418 4502 0005 1332", "project", Category::ProjectCode, 60, 0.6),
+            candidate("ES91 2100", "code", Category::ProjectCode, 80, 0.6),
+            candidate("612 345 678", "phone", Category::Phone, 50, 0.8),
+        ];
+        let resolution = LeftmostLongest.resolve(TEXT, candidates);
+        assert_eq!(values(&resolution), ["ES91 2100 0418 4502 0005 1332", "612 345 678"]);
+        let mut rules = rules(&resolution);
+        rules.sort_by_key(|(loser, _, _)| *loser);
+        assert_eq!(rules, [
+            ("code", "iban", ConflictRule::Length),
+            ("project", "iban", ConflictRule::Priority),
+            ("serial", "iban", ConflictRule::Position),
+        ]);
+    }
+
+    #[test]
+    fn full_ties_are_decided_by_detector_id() {
+        let candidates = vec![
+            candidate("Ana Ruiz", "personnel", Category::PersonnelName, 70, 0.8),
+            candidate("Ana Ruiz", "client", Category::ClientName, 70, 0.8),
+        ];
+        let resolution = LeftmostLongest.resolve(TEXT, candidates);
+        assert_eq!(resolution.accepted[0].detector_id, "client");
+        assert_eq!(rules(&resolution), [("personnel", "client", ConflictRule::DetectorId)]);
+    }
+
+    #[test]
+    fn priority_first_keeps_the_heaviest_compatible_set() {
+        let candidates = vec![
+            candidate("Pay ES91 2100 0418", "serial", Category::SerialNumber, 40, 0.5),
+            candidate("ES91 2100 0418 4502 0005 1332", "iban", Category::Iban, 100, 0.99),
+            candidate("0005 1332 to Ana", "project", Category::ProjectCode, 60, 0.6),
+            candidate("Ana Ruiz", "client", Category::ClientName, 70, 0.8),
+        ];
+        let resolution = PriorityFirst.resolve(TEXT, candidates);
+        // Leftmost-longest would keep the serial number and lose the IBAN
+        assert_eq!(values(&resolution), ["ES91 2100 0418 4502 0005 1332", "Ana Ruiz"]);
+        let mut rules = rules(&resolution);
+        rules.sort_by_key(|(loser, _, _)| *loser);
+        assert_eq!(rules, [
+            ("project", "iban", ConflictRule::Priority),
+            ("serial", "iban", ConflictRule::Priority),
+        ]);
+    }
+
+    #[test]
+    fn priority_first_reports_combined_wins() {
+        let candidates = vec![
+            candidate("ES91 2100 0418 4502 0005 1332", "iban", Category::Iban, 100, 0.99),
+            candidate("ES91 2100", "code", Category::ProjectCode, 60, 0.6),
+            candidate("0418 4502", "order", Category::WorkOrder, 60, 0.6),
+        ];
+        let resolution = PriorityFirst.resolve(TEXT, candidates);
+        assert_eq!(values(&resolution), ["ES91 2100", "0418 4502"]);
+        assert_eq!(resolution.discarded.len(), 1);
+        assert_eq!(resolution.discarded[0].loser.detector_id, "iban");
+        assert_eq!(resolution.discarded[0].rule, ConflictRule::Combined);
+    }
+
+    #[test]
+    fn confidence_first_keeps_the_highest_score() {
+        let candidates = vec![
+            candidate("Pay ES91 2100 0418", "serial", Category::SerialNumber, 40, 0.5),
+            candidate("ES91 2100 0418 4502 0005 1332", "iban", Category::Iban, 30, 0.99),
+            candidate("2100 0418 4502", "order", Category::WorkOrder, 90, 0.6),
+        ];
+        let resolution = ConfidenceFirst.resolve(TEXT, candidates);
+        assert_eq!(values(&resolution), ["ES91 2100 0418 4502 0005 1332"]);
+        let mut rules = rules(&resolution);
+        rules.sort_by_key(|(loser, _, _)| *loser);
+        assert_eq!(rules, [("order", "iban", ConflictRule::Score), ("serial", "iban", ConflictRule::Score)]);
+    }
+
+    #[test]
+    fn merge_adjacent_joins_parts_on_one_line_only() {
+        let text = "Ana  Ruiz\tPérez\n\nRuiz Pérez";
+        let part = |start: usize, value: &str| CandidateMatch {
+            span: Span { start, end: start + value.len() },
+            detector_id: "client".to_string(),
+            category: Category::ClientName,
+            priority: 70,
+            confidence: Confidence::PatternOnly,
+            score: 0.8,
+            raw_value: value.to_string(),
+            normalized_value: None,
+        };
+        let candidates = vec![part(0, "Ana"), part(5, "Ruiz"), part(10, "Pérez"), part(18, "Ruiz"), part(23, "Pérez")];
+        let resolution = MergeAdjacent.resolve(text, candidates);
+        assert_eq!(values(&resolution), ["Ana  Ruiz\tPérez", "Ruiz Pérez"]);
+        assert_eq!(resolution.accepted[0].normalized_value.as_deref(), Some("Ana Ruiz Pérez"));
+        assert_eq!(resolution.discarded.len(), 5);
+        assert!(resolution.discarded.iter().all(|c| c.rule == ConflictRule::Merged));
+    }
+
+    #[test]
+    fn merge_adjacent_keeps_categories_apart() {
+        let candidates = vec![
+            candidate("Ana Ruiz", "client", Category::ClientName, 70, 0.8),
+            candidate("Pérez", "personnel", Category::PersonnelName, 70, 0.8),
+        ];
+        let resolution = MergeAdjacent.resolve("Ana Ruiz Pérez", candidates);
+        assert_eq!(resolution.accepted.len(), 2);
+        assert!(resolution.discarded.is_empty());
+    }
+
+    #[test]
+    fn resolver_applies_its_strategy() {
+        let candidates = || vec![
+            candidate("Pay ES91 2100 0418", "serial", Category::SerialNumber, 40, 0.5),
+            candidate("ES91 2100 0418 4502 0005 1332", "iban", Category::Iban, 100, 0.99),
+        ];
+        assert_eq!(values(&ConflictResolver::default().resolve(TEXT, candidates())), ["Pay ES91 2100 0418"]);
+        let resolver = ConflictResolver::new(ResolutionStrategy::PriorityFirst.build());
+        assert_eq!(values(&resolver.resolve(TEXT, candidates())), ["ES91 2100 0418 4502 0005 1332"]);
     }
 }
@@ -0,0 +1,283 @@
+// src/context.rs
+
+use std::collections::BTreeMap;
+use crate::{AnonymizeError, Result};
+use crate::config::{ContextConfig, ContextKeywords};
+use crate::detector::CandidateMatch;
+use crate::utils::text::{fold, fold_term, is_whole_word};
+
+/// Characters ending the context window on both sides of a match
+const FIELD_BREAKS: [char; 5] = ['\n', '\r', ',', ';', '|'];
+
+/// Characters ending the context window after a match
+const SENTENCE_BREAKS: [char; 6] = ['\n', '\r', ',', ';', '|', '.'];
+
+/// Keywords looked up around the matches of each category
+struct KeywordLists {
+    boost: Vec<String>,
+    suppress: Vec<String>,
+    discard: Vec<String>,
+}
+
+impl KeywordLists {
+    fn new(keywords: &ContextKeywords) -> Self {
+        let fold_all = |terms: &[String]| -> Vec<String> {
+            terms.iter()
+                .map(|t| fold_term(t))
+                .filter(|t| !t.is_empty())
+                .collect()
+        };
+        Self {
+            boost: fold_all(&keywords.boost),
+            suppress: fold_all(&keywords.suppress),
+            discard: fold_all(&keywords.discard),
+        }
+    }
+}
+
+/// Adjusts match scores from the words around each match.
+///
+/// A few words on each side of a match are compared with keyword lists of
+/// its category, ignoring case and accents: `Tel.` or `móvil` next to a
+/// phone number raises its score, `cantidad` or `qty` lowers it, and
+/// discard keywords drop the match altogether. Runs on the original text,
+/// whose line breaks bound the window, and before the detector
+/// thresholds, so a lowered score can fall below `min_score`.
+pub struct ContextAnalyzer {
+    window: usize,
+    boost: f64,
+    penalty: f64,
+    /// Keyword lists by category placeholder name
+    categories: BTreeMap<String, KeywordLists>,
+}
+
+impl Default for ContextAnalyzer {
+    fn default() -> Self {
+        Self::from_config(&ContextConfig::default())
+            .expect("BUG: default context configuration is invalid")
+    }
+}
+
+impl ContextAnalyzer {
+    /// Build the analyzer described by `[context]`; categories without
+    /// keywords in the file keep their built-in lists
+    pub fn from_config(config: &ContextConfig) -> Result<Self> {
+        for (name, value) in [("boost", config.boost), ("penalty", config.penalty)] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(AnonymizeError::ConfigError {
+                    message: format!("context.{} must be between 0 and 1, got {}", name, value),
+                });
+            }
+        }
+
+        let mut keywords = default_keywords();
+        for (category, lists) in &config.categories {
+            keywords.insert(category.to_uppercase(), lists.clone());
+        }
+
+        Ok(Self {
+            window: if config.enabled { config.window } else { 0 },
+            boost: config.boost,
+            penalty: config.penalty,
+            categories: keywords
+                .iter()
+                .map(|(category, lists)| (category.clone(), KeywordLists::new(lists)))
+                .collect(),
+        })
+    }
+
+    /// Adjust the scores of `candidates` found in `text`, returning the
+    /// kept candidates and the number discarded
+    pub fn apply(&self, text: &str, candidates: Vec<CandidateMatch>) -> (Vec<CandidateMatch>, usize) {
+        if self.window == 0 {
+            return (candidates, 0);
+        }
+
+        let before = candidates.len();
+        let kept: Vec<CandidateMatch> = candidates
+            .into_iter()
+            .filter_map(|mut candidate| {
+                let Some(lists) = self.categories.get(&candidate.category.placeholder_name()) else {
+                    return Some(candidate);
+                };
+                let context = self.window_around(text, &candidate);
+
+                if contains_any(&context, &lists.discard) {
+                    return None;
+                }
+                if contains_any(&context, &lists.boost) {
+                    candidate.score = (candidate.score + self.boost).min(1.0);
+                }
+                if contains_any(&context, &lists.suppress) {
+                    candidate.score = (candidate.score - self.penalty).max(0.0);
+                }
+                // Keep scores readable in reports (0.9, not 0.8999999999999999)
+                candidate.score = (candidate.score * 1000.0).round() / 1000.0;
+                Some(candidate)
+            })
+            .collect();
+
+        let discarded = before - kept.len();
+        (kept, discarded)
+    }
+
+    /// Folded words before and after a match, separated by the match itself
+    /// so that a keyword cannot span it. The window stays within the
+    /// line, field and sentence of the match: it stops at line breaks,
+    /// at `,` `;` `|` and at periods ending a sentence
+    fn window_around(&self, text: &str, candidate: &CandidateMatch) -> String {
+        let before = &text[..candidate.span.start];
+        let before = before
+            .rfind(FIELD_BREAKS)
+            .map_or(before, |i| &before[i + 1..]);
+        let after = &text[candidate.span.end..];
+        let after = after
+            .find(SENTENCE_BREAKS)
+            .map_or(after, |i| &after[..i]);
+
+        // A period ends the preceding sentence unless it follows a short
+        // abbreviation (`Tel.`, `Ref.`, `Nº.`)
+        let mut preceding: Vec<&str> = before
+            .split_whitespace()
+            .rev()
+            .take_while(|word| !(word.ends_with('.') && word.chars().count() > 5))
+            .take(self.window)
+            .collect();
+        preceding.reverse();
+        let following: Vec<&str> = after.split_whitespace().take(self.window).collect();
+
+        fold(&format!("{} \u{1} {}", preceding.join(" "), following.join(" "))).0
+    }
+}
+
+/// Whether `text` contains one of the keywords as whole words
+fn contains_any(text: &str, keywords: &[String]) -> bool {
+    keywords.iter().any(|keyword| {
+        text.match_indices(keyword.as_str())
+            .any(|(i, _)| is_whole_word(text, i, i + keyword.len()))
+    })
+}
+
+fn default_keywords() -> BTreeMap<String, ContextKeywords> {
+    let list = |terms: &[&str]| terms.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+    let rule = |boost: &[&str], suppress: &[&str]| ContextKeywords {
+        boost: list(boost),
+        suppress: list(suppress),
+        discard: Vec::new(),
+    };
+
+    let mut keywords = BTreeMap::new();
+    keywords.insert("PHONE".to_string(), rule(
+        &["tel", "telf", "tlf", "teléfono", "móvil", "fax", "contacto", "llamar",
+          "phone", "telephone", "mobile", "cell", "call"],
+        &["cantidad", "unidades", "uds", "importe", "total", "pedido", "factura", "ref", "referencia",
+          "qty", "quantity", "units", "amount", "order", "invoice", "part"],
+    ));
+    keywords.insert("EMAIL".to_string(), rule(&["email", "e-mail", "correo", "mail"], &[]));
+    keywords.insert("IBAN".to_string(), rule(
+        &["iban", "cuenta", "transferencia", "banco", "account", "transfer", "bank"],
+        &[],
+    ));
+    keywords.insert("NATIONAL_ID".to_string(), rule(
+        &["dni", "nie", "nif", "documento", "identidad", "id", "identity", "ssn", "social security"],
+        &[],
+    ));
+    keywords.insert("CREDIT_CARD".to_string(), rule(
+        &["tarjeta", "visa", "mastercard", "amex", "card"],
+        &[],
+    ));
+    keywords.insert("SERIAL_NUMBER".to_string(), rule(
+        &["serie", "n/s", "s/n", "sn", "serial"],
+        &["pieza", "p/n", "pn", "part number", "part"],
+    ));
+    keywords.insert("PURCHASE_ORDER".to_string(), rule(
+        &["pedido", "ref. pedido", "orden de compra", "purchase order", "po"],
+        &[],
+    ));
+    keywords
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detector::{Category, Confidence, Span};
+    use crate::{Anonymizer, AnonymizeConfig};
+
+    fn analyzer(toml: &str) -> Result<ContextAnalyzer> {
+        ContextAnalyzer::from_config(&toml::from_str(toml).unwrap())
+    }
+
+    /// Phone candidate on the first occurrence of `value` in `text`
+    fn phone(text: &str, value: &str, score: f64) -> CandidateMatch {
+        let start = text.find(value).unwrap();
+        CandidateMatch {
+            span: Span { start, end: start + value.len() },
+            detector_id: "phone".to_string(),
+            category: Category::Phone,
+            priority: 30,
+            confidence: Confidence::PatternOnly,
+            score,
+            raw_value: value.to_string(),
+            normalized_value: None,
+        }
+    }
+
+    fn score(analyzer: &ContextAnalyzer, text: &str) -> f64 {
+        let (kept, _) = analyzer.apply(text, vec![phone(text, "612 345 678", 0.5)]);
+        kept[0].score
+    }
+
+    #[test]
+    fn keywords_count_within_the_window_only() {
+        let analyzer = ContextAnalyzer::default();
+        assert_eq!(score(&analyzer, "Tel. 612 345 678"), 0.7);
+        assert_eq!(score(&analyzer, "MÓVIL: 612 345 678"), 0.7);
+        assert_eq!(score(&analyzer, "612 345 678 (llamar por la tarde)"), 0.7);
+        assert_eq!(score(&analyzer, "Tel. de la oficina central 612 345 678"), 0.5);
+        assert_eq!(score(&analyzer, "Tel.\n612 345 678"), 0.5);
+        assert_eq!(score(&analyzer, "Telefonía 612 345 678"), 0.5);
+        assert_eq!(score(&analyzer, "Cantidad: 612 345 678"), 0.2);
+    }
+
+    #[test]
+    fn adjusted_scores_stay_between_0_and_1() {
+        let text = "Tel. 612 345 678, Total 612 345 679";
+        let (kept, _) = analyzer("boost = 0.5\npenalty = 0.9").unwrap().apply(text, vec![phone(text, "612 345 678", 0.8), phone(text, "612 345 679", 0.5)]);
+        assert_eq!(kept[0].score, 1.0);
+        assert_eq!(kept[1].score, 0.0);
+
+        assert!(matches!(analyzer("boost = 1.5"), Err(AnonymizeError::ConfigError { .. })));
+        assert!(matches!(analyzer("penalty = -0.1"), Err(AnonymizeError::ConfigError { .. })));
+    }
+
+    #[test]
+    fn discard_keywords_drop_the_match() {
+        let analyzer = analyzer("[categories.phone]\nboost = [\"tel\"]\ndiscard = [\"ISBN\"]").unwrap();
+        let text = "ISBN 612 345 678, tel 612 345 679";
+        let (kept, discarded) = analyzer.apply(text, vec![phone(text, "612 345 678", 0.5), phone(text, "612 345 679", 0.5)]);
+        assert_eq!(discarded, 1);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].raw_value, "612 345 679");
+        assert_eq!(kept[0].score, 0.7);
+    }
+
+    #[test]
+    fn disabled_analysis_leaves_scores_alone() {
+        let analyzer = analyzer("enabled = false").unwrap();
+        assert_eq!(score(&analyzer, "Cantidad: 612 345 678"), 0.5);
+    }
+
+    #[test]
+    fn penalized_matches_fall_below_min_score() {
+        let config = AnonymizeConfig::from_toml_str(concat!(
+            "[[custom_detectors]]\nid = \"lot\"\npattern = \"LT-[0-9]{4}\"\ncategory = \"LOT_CODE\"\nscore = 0.6\n",
+            "[detectors.lot]\nmin_score = 0.5\n",
+            "[context.categories.LOT_CODE]\nsuppress = [\"plano\"]\n",
+        )).unwrap();
+        let anonymizer = Anonymizer::from_config(&config).unwrap();
+
+        let output = anonymizer.anonymize("Lote LT-0042, plano LT-0043").unwrap();
+        assert_eq!(output.text, "Lote [LOT_CODE_001], plano LT-0043");
+        assert_eq!(output.report.statistics.below_threshold, 1);
+    }
+}
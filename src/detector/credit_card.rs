@@ -49,6 +49,7 @@ impl Detector for CreditCardDetector {
                         category: self.category(),
                         priority: self.priority(),
                         confidence: Confidence::Verified,
+                        score: 0.95,
                         raw_value: raw.to_string(),
                         normalized_value: Some(normalize_card(raw)),
                     })
@@ -0,0 +1,268 @@
+// src/detector/document_number.rs
+
+use regex::Regex;
+use crate::{AnonymizeError, Result};
+use crate::config::DocumentTemplateConfig;
+use crate::detector::{Detector, CandidateMatch, Category, Span, DetectorId, Confidence, ValidationResult};
+
+/// Template used when none is configured: project, unit, discipline,
+/// document type and sequence, e.g. `ABC-123-ME-DWG-0001`
+pub const DEFAULT_DOCUMENT_TEMPLATE: &str = "A{2,5}-9{2,5}-A{2,3}-A{3}-9{3,5}";
+
+/// Revision suffix accepted after a code, e.g. ` Rev. B`, `_REV01`, `-Rev 2`.
+/// A letter revision needs a dot or space after `Rev`, so words such as
+/// `REVIEW` are not read as revision `IEW`.
+const REVISION_PATTERN: &str =
+    r"(?P<suffix>[ _-]?(?i:rev)(?:(?:\. ?| )(?P<rev>[A-Z0-9]{1,3})|(?P<rev_number>[0-9]{1,3})))";
+
+/// Detects document references built from numbering templates.
+///
+/// A template lists the segments of a code and the separators between
+/// them: `A` is an upper-case letter, `9` a digit and `X` either, each
+/// optionally repeated with `{n}` or `{m,n}`; `(ME|EL|CI)` lists literal
+/// codes. Any other character (`-`, `_`, `.`, `/`) is a separator, so
+/// `A{3}-9{3}-(ME|EL)-(DWG|SPC)-9{4}` matches `ABC-123-ME-DWG-0001`.
+pub struct DocumentNumberDetector {
+    patterns: Vec<Regex>,
+}
+
+impl Default for DocumentNumberDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DocumentNumberDetector {
+    pub fn new() -> Self {
+        Self::from_templates(&[DocumentTemplateConfig {
+            template: DEFAULT_DOCUMENT_TEMPLATE.to_string(),
+            revision: true,
+        }])
+        .expect("BUG: default document template is invalid")
+    }
+
+    /// Detector for the templates of `[[document_numbers]]`
+    pub fn from_templates(templates: &[DocumentTemplateConfig]) -> Result<Self> {
+        let patterns = templates
+            .iter()
+            .map(|t| {
+                let code = template_regex(&t.template)?;
+                // The suffix may be glued with `_`, which `\b` treats as a word character
+                let pattern = if t.revision {
+                    format!(r"\b{}(?:{}\b|\b)", code, REVISION_PATTERN)
+                } else {
+                    format!(r"\b{}\b", code)
+                };
+                Regex::new(&pattern).map_err(|e| invalid_template(&t.template, &e.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { patterns })
+    }
+}
+
+fn invalid_template(template: &str, message: &str) -> AnonymizeError {
+    AnonymizeError::InvalidPattern {
+        detector: "document_number".to_string(),
+        message: format!("template '{}': {}", template, message),
+    }
+}
+
+/// Translate a numbering template into a regular expression
+fn template_regex(template: &str) -> Result<String> {
+    let mut regex = String::new();
+    let mut chars = template.chars().peekable();
+    // Whether the last element is a character class that `{...}` may repeat
+    let mut repeatable = false;
+
+    while let Some(c) = chars.next() {
+        let class = match c {
+            'A' => Some("[A-Z]"),
+            '9' => Some("[0-9]"),
+            'X' => Some("[A-Z0-9]"),
+            _ => None,
+        };
+        if let Some(class) = class {
+            regex.push_str(class);
+            repeatable = true;
+            continue;
+        }
+        match c {
+            '(' => {
+                let mut codes = String::new();
+                loop {
+                    match chars.next() {
+                        Some(')') => break,
+                        Some(c) => codes.push(c),
+                        None => return Err(invalid_template(template, "unclosed '('")),
+                    }
+                }
+                let alternatives: Vec<String> = codes.split('|').map(|code| regex::escape(code.trim())).collect();
+                if alternatives.iter().any(String::is_empty) {
+                    return Err(invalid_template(template, "empty code in '(...)'"));
+                }
+                regex.push_str(&format!("(?:{})", alternatives.join("|")));
+            }
+            '{' => {
+                let mut quantifier = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) if c.is_ascii_digit() || c == ',' => quantifier.push(c),
+                        _ => return Err(invalid_template(template, "malformed '{n}' or '{m,n}'")),
+                    }
+                }
+                if !repeatable {
+                    return Err(invalid_template(template, "'{...}' must follow A, 9 or X"));
+                }
+                let bounds: Vec<&str> = quantifier.split(',').collect();
+                let valid = match bounds.as_slice() {
+                    [n] => n.parse::<u32>().is_ok_and(|n| n > 0),
+                    [m, n] => match (m.parse::<u32>(), n.parse::<u32>()) {
+                        (Ok(m), Ok(n)) => m <= n && n > 0,
+                        _ => false,
+                    },
+                    _ => false,
+                };
+                if !valid {
+                    return Err(invalid_template(template, "malformed '{n}' or '{m,n}'"));
+                }
+                regex.push_str(&format!("{{{}}}", quantifier));
+            }
+            c if c.is_alphanumeric() => {
+                return Err(invalid_template(
+                    template,
+                    &format!("unexpected '{}', write literal codes as '({})'", c, c),
+                ));
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        repeatable = false;
+    }
+
+    if regex.is_empty() {
+        return Err(invalid_template(template, "template is empty"));
+    }
+    Ok(regex)
+}
+
+impl Detector for DocumentNumberDetector {
+    fn id(&self) -> DetectorId {
+        "document_number".to_string()
+    }
+
+    fn category(&self) -> Category {
+        Category::DocumentNumber
+    }
+
+    fn detect(&self, text: &str) -> Vec<CandidateMatch> {
+        let mut all_matches = Vec::new();
+        for pattern in &self.patterns {
+            let matches = pattern
+                .captures_iter(text)
+                .map(|caps| {
+                    let m = caps.get(0).expect("BUG: capture group 0 always exists");
+                    // Same document and revision however the suffix is written
+                    let suffix_len = caps.name("suffix").map_or(0, |s| s.len());
+                    let code = &m.as_str()[..m.len() - suffix_len];
+                    let normalized = match caps.name("rev").or_else(|| caps.name("rev_number")) {
+                        Some(rev) => format!("{} REV {}", code, rev.as_str().to_uppercase()),
+                        None => code.to_string(),
+                    };
+                    CandidateMatch {
+                        span: Span {
+                            start: m.start(),
+                            end: m.end(),
+                        },
+                        detector_id: self.id(),
+                        category: self.category(),
+                        priority: self.priority(),
+                        confidence: Confidence::PatternOnly,
+                        score: 0.85,
+                        raw_value: m.as_str().to_string(),
+                        normalized_value: Some(normalized),
+                    }
+                })
+                .collect::<Vec<_>>();
+            all_matches.extend(matches);
+        }
+        all_matches
+    }
+
+    fn validate(&self, _candidate: &str) -> ValidationResult {
+        ValidationResult::NotApplicable
+    }
+
+    fn priority(&self) -> u32 {
+        75
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector(template: &str, revision: bool) -> DocumentNumberDetector {
+        DocumentNumberDetector::from_templates(&[DocumentTemplateConfig {
+            template: template.to_string(),
+            revision,
+        }])
+        .unwrap()
+    }
+
+    fn found(detector: &DocumentNumberDetector, text: &str) -> Vec<(String, String)> {
+        detector.detect(text)
+            .into_iter()
+            .map(|m| (m.raw_value, m.normalized_value.unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn templates_translate_to_regexes() {
+        assert_eq!(template_regex("A{3}-9{2,4}").unwrap(), r"[A-Z]{3}\-[0-9]{2,4}");
+        assert_eq!(template_regex("(ME|EL)_X.9").unwrap(), r"(?:ME|EL)_[A-Z0-9]\.[0-9]");
+        assert_eq!(template_regex("(ME | E.L)").unwrap(), r"(?:ME|E\.L)");
+        assert!(template_regex(DEFAULT_DOCUMENT_TEMPLATE).is_ok());
+    }
+
+    #[test]
+    fn malformed_templates_are_rejected() {
+        for template in [
+            "", "A-]{3}", "(ME){2}", "-{2}", "{2}", "A{2}{3}", "A{}", "A{,3}", "A{3,1}", "A{0}", "A{x}",
+            "A{2", "(ME", "(ME|)", "ABC-9{3}", "A-B",
+        ] {
+            assert!(
+                matches!(template_regex(template), Err(AnonymizeError::InvalidPattern { .. })),
+                "accepted '{}'", template
+            );
+        }
+    }
+
+    #[test]
+    fn revisions_are_part_of_the_reference() {
+        let detector = detector("A{3}-9{4}", true);
+        let text = "See ABC-0001 Rev. B, ABC-0001_REV B, abc-0001, ABC-0002-Rev 2 and ABC-0003_REV01.";
+        assert_eq!(found(&detector, text), [
+            ("ABC-0001 Rev. B".to_string(), "ABC-0001 REV B".to_string()),
+            ("ABC-0001_REV B".to_string(), "ABC-0001 REV B".to_string()),
+            ("ABC-0002-Rev 2".to_string(), "ABC-0002 REV 2".to_string()),
+            ("ABC-0003_REV01".to_string(), "ABC-0003 REV 01".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn words_starting_with_rev_are_not_revisions() {
+        let detector = detector("A{3}-9{4}", true);
+        assert_eq!(found(&detector, "ABC-0001 REVIEW pending, ABC-0002 Revised"), [
+            ("ABC-0001".to_string(), "ABC-0001".to_string()),
+            ("ABC-0002".to_string(), "ABC-0002".to_string()),
+        ]);
+        assert!(found(&detector, "ABC-0001_REVIEW").is_empty());
+        assert!(found(&detector, "ABC-0001 Rev. ABCD").iter().all(|(raw, _)| raw == "ABC-0001"));
+    }
+
+    #[test]
+    fn suffixes_are_ignored_without_revisions() {
+        let detector = detector("A{3}-9{4}", false);
+        assert_eq!(found(&detector, "ABC-0001 Rev. B"), [("ABC-0001".to_string(), "ABC-0001".to_string())]);
+    }
+}
@@ -42,6 +42,7 @@ impl Detector for EmailDetector {
                 category: Category::Email,
                 priority: self.priority(),
                 confidence: Confidence::PatternOnly,
+                score: 0.9,
                 raw_value: m.as_str().to_string(),
                 normalized_value: None,
             })
@@ -0,0 +1,243 @@
+// src/detector/gazetteer.rs
+
+use aho_corasick::{AhoCorasick, MatchKind};
+use std::collections::HashMap;
+use std::path::Path;
+use crate::{AnonymizeError, Result};
+use crate::config::GazetteerConfig;
+use crate::detector::{Detector, CandidateMatch, Category, Span, DetectorId, Confidence, ValidationResult};
+use crate::utils::text::{fold, fold_term, is_whole_word};
+
+/// Detects the entries of a term list (employees, clients, companies,
+/// projects) wherever they appear as whole words.
+///
+/// Matching ignores case and accents, so `Jose Martinez` in the list
+/// finds `JOSÉ MARTÍNEZ` in the text. All terms are compiled into a
+/// single Aho-Corasick automaton, which keeps scanning linear in the text
+/// size for lists of tens of thousands of entries.
+pub struct GazetteerDetector {
+    id: DetectorId,
+    category: Category,
+    automaton: AhoCorasick,
+    /// Spelling of each pattern as first listed, used as normalized value
+    /// so every spelling of a term shares one placeholder
+    canonical: Vec<String>,
+}
+
+impl GazetteerDetector {
+    pub fn new<I, S>(id: impl Into<String>, category: Category, terms: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let id = id.into();
+        let mut patterns = Vec::new();
+        let mut canonical = Vec::new();
+        let mut seen = HashMap::new();
+        for term in terms {
+            let term = term.as_ref().trim();
+            let folded = fold_term(term);
+            if folded.is_empty() || seen.contains_key(&folded) {
+                continue;
+            }
+            seen.insert(folded.clone(), canonical.len());
+            patterns.push(folded);
+            canonical.push(term.split_whitespace().collect::<Vec<_>>().join(" "));
+        }
+
+        let automaton = AhoCorasick::builder()
+            .match_kind(MatchKind::Standard)
+            .build(&patterns)
+            .map_err(|e| AnonymizeError::ConfigError {
+                message: format!("Gazetteer '{}': {}", id, e),
+            })?;
+
+        Ok(Self {
+            id,
+            category,
+            automaton,
+            canonical,
+        })
+    }
+
+    /// Build the detector described by `[gazetteers.<CATEGORY>]`; its id
+    /// is `gazetteer_<category>`
+    pub fn from_config(category: &str, config: &GazetteerConfig) -> Result<Self> {
+        let mut terms = config.terms.clone();
+        for file in &config.files {
+            terms.extend(load_terms(file)?);
+        }
+        Self::new(
+            format!("gazetteer_{}", category.to_lowercase()),
+            Category::from_placeholder_name(category),
+            terms,
+        )
+    }
+
+    /// Number of distinct terms in the list
+    pub fn len(&self) -> usize {
+        self.canonical.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.canonical.is_empty()
+    }
+}
+
+/// Read a term list: one term per line, blank lines and `#` comments ignored
+fn load_terms(path: impl AsRef<Path>) -> Result<Vec<String>> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path).map_err(|e| AnonymizeError::ConfigError {
+        message: format!("Cannot read term list {}: {}", path.display(), e),
+    })?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect())
+}
+
+impl Detector for GazetteerDetector {
+    fn id(&self) -> DetectorId {
+        self.id.clone()
+    }
+
+    fn category(&self) -> Category {
+        self.category.clone()
+    }
+
+    fn detect(&self, text: &str) -> Vec<CandidateMatch> {
+        if self.canonical.is_empty() {
+            return Vec::new();
+        }
+        let (folded, offsets) = fold(text);
+
+        // Every whole-word hit, then the leftmost-longest non-overlapping
+        // ones, so "Acme" cannot hide "Acme Ingeniería" nor vice versa
+        // when only one of them is on word boundaries
+        let mut hits: Vec<_> = self.automaton
+            .find_overlapping_iter(&folded)
+            .filter(|m| is_whole_word(&folded, m.start(), m.end()))
+            .collect();
+        hits.sort_by_key(|m| (m.start(), std::cmp::Reverse(m.end())));
+
+        let mut matches = Vec::new();
+        let mut last_end = 0;
+        for hit in hits {
+            if hit.start() < last_end {
+                continue;
+            }
+            last_end = hit.end();
+
+            let span = offsets.to_original(Span {
+                start: hit.start(),
+                end: hit.end(),
+            });
+            matches.push(CandidateMatch {
+                span,
+                detector_id: self.id(),
+                category: self.category(),
+                priority: self.priority(),
+                confidence: Confidence::Verified,
+                score: 0.9,
+                raw_value: text[span.start..span.end].to_string(),
+                normalized_value: Some(self.canonical[hit.pattern().as_usize()].clone()),
+            });
+        }
+        matches
+    }
+
+    fn validate(&self, _candidate: &str) -> ValidationResult {
+        ValidationResult::NotApplicable
+    }
+
+    fn priority(&self) -> u32 {
+        55
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clients(terms: &[&str]) -> GazetteerDetector {
+        GazetteerDetector::new("gazetteer_client_name", Category::ClientName, terms).unwrap()
+    }
+
+    fn found(detector: &GazetteerDetector, text: &str) -> Vec<(String, String)> {
+        detector
+            .detect(text)
+            .into_iter()
+            .map(|m| (m.raw_value, m.normalized_value.unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn matching_folds_case_and_accents() {
+        let detector = clients(&["Jose Martinez"]);
+        assert_eq!(
+            found(&detector, "Firmado: JOSÉ MARTÍNEZ, jefe de obra"),
+            vec![("JOSÉ MARTÍNEZ".to_string(), "Jose Martinez".to_string())]
+        );
+    }
+
+    #[test]
+    fn spellings_share_the_first_listed_term() {
+        let detector = clients(&["Acme  Ingeniería", "ACME INGENIERIA"]);
+        assert_eq!(detector.len(), 1);
+        assert_eq!(
+            found(&detector, "acme ingenieria"),
+            vec![("acme ingenieria".to_string(), "Acme Ingeniería".to_string())]
+        );
+    }
+
+    #[test]
+    fn terms_match_whole_words_only() {
+        let detector = clients(&["Acme"]);
+        assert!(found(&detector, "Acmex y SuperAcme").is_empty());
+        assert_eq!(found(&detector, "(Acme).").len(), 1);
+    }
+
+    #[test]
+    fn overlapping_terms_keep_the_longest() {
+        let detector = clients(&["Acme", "Acme Ingeniería", "Ingeniería Norte"]);
+        assert_eq!(
+            found(&detector, "Contrato con Acme Ingeniería Norte"),
+            vec![("Acme Ingeniería".to_string(), "Acme Ingeniería".to_string())]
+        );
+        // The longer term is not on word boundaries, so the shorter one wins
+        assert_eq!(
+            found(&detector, "Acme Ingenierías"),
+            vec![("Acme".to_string(), "Acme".to_string())]
+        );
+    }
+
+    #[test]
+    fn term_files_skip_blank_lines_and_comments() {
+        let path = std::env::temp_dir().join(format!("anonymize-gazetteer-{}.txt", std::process::id()));
+        std::fs::write(&path, "# clients\nAcme\n\n  Globex  \n").unwrap();
+        let config = GazetteerConfig {
+            files: vec![path.display().to_string()],
+            terms: vec!["Initech".to_string()],
+        };
+        let detector = GazetteerDetector::from_config("CLIENT_NAME", &config).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(detector.id(), "gazetteer_client_name");
+        assert_eq!(detector.len(), 3);
+        assert_eq!(found(&detector, "Globex").len(), 1);
+    }
+
+    #[test]
+    fn missing_term_file_is_a_config_error() {
+        let config = GazetteerConfig {
+            files: vec!["/nonexistent/clients.txt".to_string()],
+            terms: Vec::new(),
+        };
+        assert!(matches!(
+            GazetteerDetector::from_config("CLIENT_NAME", &config),
+            Err(AnonymizeError::ConfigError { .. })
+        ));
+    }
+}
@@ -49,6 +49,7 @@ impl Detector for IbanDetector {
                         category: self.category(),
                         priority: self.priority(),
                         confidence: Confidence::Verified,
+                        score: 0.99,
                         raw_value: raw.to_string(),
                         normalized_value: Some(normalize_iban(raw)),
                     })
@@ -0,0 +1,267 @@
+// src/detector/label.rs
+
+use aho_corasick::{AhoCorasick, MatchKind};
+use crate::{AnonymizeError, Result};
+use crate::config::LabelsConfig;
+use crate::normalizer::{NormalizedText, OffsetMap};
+use crate::detector::{Detector, CandidateMatch, Category, Span, DetectorId, Confidence, ValidationResult};
+use crate::utils::text::{fold, fold_term, is_whole_word};
+
+/// Lower-case words allowed inside a name (`María de la Fuente`)
+const NAME_PARTICLES: &[&str] = &[
+    "de", "del", "la", "las", "los", "y", "i", "da", "das", "do", "dos",
+    "di", "du", "le", "van", "von", "der", "den", "zu",
+];
+
+/// Characters allowed between a label and the name (`Revisado por: ...`)
+const LABEL_SEPARATORS: &[char] = &[':', '-', '–', '—', '='];
+
+/// Characters that end a field; the name stops at the first one
+const FIELD_DELIMITERS: &[char] = &[',', ';', '|', '(', ')', '[', ']', '/'];
+
+/// Captures the name following a sign-off label of a title block, such as
+/// `Revisado por: J. Martínez` or `Approved by K. Schmidt`.
+///
+/// Labels are matched ignoring case and accents. The name is the run of
+/// capitalized words and initials (with particles such as `de la`) after
+/// the label, and stops at a line break of the input, a field delimiter, a
+/// lower-case word, a word ending in `:`, another label or a stop word
+/// such as `Fecha`, after `max_words` words at most. Called with `detect`
+/// alone, on whitespace-collapsed text, the end of the line is inferred
+/// from those rules only.
+pub struct LabelDetector {
+    id: DetectorId,
+    category: Category,
+    labels: AhoCorasick,
+    /// Labels of every sign-off category, which end a name
+    stop_labels: Vec<String>,
+    stop_words: Vec<String>,
+    max_words: usize,
+}
+
+impl LabelDetector {
+    pub fn new<S: AsRef<str>>(
+        id: impl Into<String>,
+        category: Category,
+        labels: &[S],
+        stop_labels: &[S],
+        stop_words: &[S],
+        max_words: usize,
+    ) -> Result<Self> {
+        let fold_all = |terms: &[S]| -> Vec<String> {
+            terms.iter()
+                .map(|t| fold_term(t.as_ref()))
+                .filter(|t| !t.is_empty())
+                .collect()
+        };
+
+        let id = id.into();
+        let labels = AhoCorasick::builder()
+            .match_kind(MatchKind::LeftmostLongest)
+            .build(fold_all(labels))
+            .map_err(|e| AnonymizeError::ConfigError {
+                message: format!("Labels of '{}': {}", id, e),
+            })?;
+
+        Ok(Self {
+            id,
+            category,
+            labels,
+            stop_labels: fold_all(stop_labels),
+            stop_words: fold_all(stop_words),
+            max_words,
+        })
+    }
+
+    /// Detector `revised_by`, `approved_by` or `designed_by` with the
+    /// labels of `[labels]`; `None` for any other id
+    pub fn from_config(id: &str, config: &LabelsConfig) -> Result<Option<Self>> {
+        let (category, labels) = match id {
+            "revised_by" => (Category::RevisedBy, &config.revised_by),
+            "approved_by" => (Category::ApprovedBy, &config.approved_by),
+            "designed_by" => (Category::DesignedBy, &config.designed_by),
+            _ => return Ok(None),
+        };
+        let all_labels: Vec<String> = config.revised_by.iter()
+            .chain(&config.approved_by)
+            .chain(&config.designed_by)
+            .cloned()
+            .collect();
+        Self::new(id, category, labels, &all_labels, &config.stop_words, config.max_words).map(Some)
+    }
+
+    /// Names following the labels of `text`; `breaks_line` tells whether
+    /// a whitespace span of `text` stands for a line break of the input
+    fn detect_names(&self, text: &str, breaks_line: &dyn Fn(Span) -> bool) -> Vec<CandidateMatch> {
+        let (folded, offsets) = fold(text);
+
+        self.labels
+            .find_iter(&folded)
+            .filter(|m| is_whole_word(&folded, m.start(), m.end()))
+            .filter_map(|m| {
+                let after_label = folded[m.end()..]
+                    .trim_start_matches(|c: char| c.is_whitespace() || LABEL_SEPARATORS.contains(&c));
+                let start = folded.len() - after_label.len();
+                let name = self.capture_name(text, &folded, &offsets, breaks_line, start)?;
+                let span = offsets.to_original(name);
+
+                Some(CandidateMatch {
+                    span,
+                    detector_id: self.id(),
+                    category: self.category(),
+                    priority: self.priority(),
+                    confidence: Confidence::PatternOnly,
+                    score: 0.8,
+                    raw_value: text[span.start..span.end].to_string(),
+                    normalized_value: None,
+                })
+            })
+            .collect()
+    }
+
+    /// Span (in folded text) of the name starting at `pos`, if any
+    fn capture_name(
+        &self,
+        text: &str,
+        folded: &str,
+        offsets: &OffsetMap,
+        breaks_line: &dyn Fn(Span) -> bool,
+        mut pos: usize,
+    ) -> Option<Span> {
+        let mut name: Option<Span> = None;
+        let mut has_full_word = false;
+        let mut words = 0;
+
+        while words < self.max_words {
+            let gap = Span { start: pos, end: pos + folded[pos..].len() - folded[pos..].trim_start().len() };
+            // The name may start on the line after the label, not continue
+            // on the next one
+            if words > 0 && gap.end > gap.start && breaks_line(offsets.to_original(gap)) {
+                break;
+            }
+            pos = gap.end;
+            let rest = &folded[pos..];
+            if rest.is_empty() || self.starts_with_any(folded, pos, &self.stop_labels) {
+                break;
+            }
+
+            let word_len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let word = &rest[..word_len];
+            if word.ends_with(':') {
+                break;
+            }
+
+            // Cut the word at a field delimiter; the name ends there
+            let (word, delimited) = match word.find(FIELD_DELIMITERS) {
+                Some(i) => (&word[..i], true),
+                None => (word, false),
+            };
+            if word.is_empty() || self.stop_words.iter().any(|w| w == word.trim_end_matches('.')) {
+                break;
+            }
+
+            let original = offsets.to_original(Span { start: pos, end: pos + word.len() });
+            let first = text[original.start..original.end].chars().next();
+            let letters = word.chars().filter(|c| c.is_alphabetic()).count();
+            let is_initial = word.ends_with('.') && letters <= 2;
+            let capitalized = first.is_some_and(|c| c.is_uppercase());
+
+            if capitalized && letters > 0 {
+                // A trailing period ends the sentence unless the word is an initial
+                let end = if is_initial { word.len() } else { word.trim_end_matches('.').len() };
+                has_full_word |= !is_initial;
+                let start = name.map_or(pos, |n| n.start);
+                name = Some(Span { start, end: pos + end });
+                if word.len() != end {
+                    break;
+                }
+            } else if !(name.is_some() && NAME_PARTICLES.contains(&word)) {
+                break;
+            }
+
+            words += 1;
+            pos += word.len();
+            if delimited {
+                break;
+            }
+        }
+
+        name.filter(|_| has_full_word)
+    }
+
+    fn starts_with_any(&self, folded: &str, pos: usize, terms: &[String]) -> bool {
+        terms.iter().any(|t| {
+            folded[pos..].starts_with(t.as_str()) && is_whole_word(folded, pos, pos + t.len())
+        })
+    }
+}
+
+impl Detector for LabelDetector {
+    fn id(&self) -> DetectorId {
+        self.id.clone()
+    }
+
+    fn category(&self) -> Category {
+        self.category.clone()
+    }
+
+    fn detect(&self, text: &str) -> Vec<CandidateMatch> {
+        self.detect_names(text, &|gap| text[gap.start..gap.end].contains(['\n', '\r']))
+    }
+
+    fn detect_normalized(&self, normalized: &NormalizedText, original: &str) -> Vec<CandidateMatch> {
+        self.detect_names(&normalized.content, &|gap| {
+            let gap = normalized.offsets.to_original(gap);
+            original[gap.start..gap.end].contains(['\n', '\r'])
+        })
+    }
+
+    fn validate(&self, _candidate: &str) -> ValidationResult {
+        ValidationResult::NotApplicable
+    }
+
+    fn priority(&self) -> u32 {
+        75
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normalizer::normalize;
+
+    fn revised_by() -> LabelDetector {
+        LabelDetector::from_config("revised_by", &LabelsConfig::default()).unwrap().unwrap()
+    }
+
+    fn names(text: &str) -> Vec<String> {
+        let normalized = normalize(text).unwrap();
+        revised_by()
+            .detect_normalized(&normalized, text)
+            .iter()
+            .map(|m| {
+                let span = normalized.offsets.to_original(m.span);
+                text[span.start..span.end].to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn name_stops_at_line_break() {
+        assert_eq!(names("Revisado por: J. Martínez\nProyecto Alfa"), ["J. Martínez"]);
+        assert_eq!(names("Revisado por: J. Martínez\r\n  Proyecto Alfa"), ["J. Martínez"]);
+        assert_eq!(names("Revised by K. Schmidt, 2024"), ["K. Schmidt"]);
+    }
+
+    #[test]
+    fn name_may_start_on_next_line() {
+        assert_eq!(names("Revisado por:\n  María de la Fuente\nFecha: 2024"), ["María de la Fuente"]);
+    }
+
+    #[test]
+    fn raw_text_line_breaks_end_names_too() {
+        let matches = revised_by().detect("Revisado por: J. Martínez\nProyecto Alfa");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].raw_value, "J. Martínez");
+    }
+}
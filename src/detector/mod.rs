@@ -12,6 +12,10 @@ mod work_order;
 mod purchase_order;
 mod serial_number;
 mod cost_center;
+mod regex_detector;
+mod gazetteer;
+mod label;
+mod document_number;
 
 pub use email::EmailDetector;
 pub use phone::PhoneDetector;
@@ -25,8 +29,14 @@ pub use work_order::WorkOrderDetector;
 pub use purchase_order::PurchaseOrderDetector;
 pub use serial_number::SerialNumberDetector;
 pub use cost_center::CostCenterDetector;
+pub use regex_detector::{RegexDetector, ChecksumValidator, DEFAULT_CUSTOM_SCORE};
+pub use gazetteer::GazetteerDetector;
+pub use label::LabelDetector;
+pub use document_number::{DocumentNumberDetector, DEFAULT_DOCUMENT_TEMPLATE};
 
 use serde::{Serialize, Deserialize};
+use crate::config::LabelsConfig;
+use crate::normalizer::NormalizedText;
 
 /// Unique identifier for a detector
 pub type DetectorId = String;
@@ -38,8 +48,12 @@ pub struct Span {
     pub end: usize,
 }
 
-/// Category of detected sensitive data
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Category of detected sensitive data.
+///
+/// Serialized as its placeholder name (`"EMAIL"`, `"NATIONAL_ID"`, or the
+/// upper-cased name of a custom category), which is part of the report
+/// format and does not change with the variant names.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Category {
     Email,
     Phone,
@@ -56,6 +70,9 @@ pub enum Category {
     ProjectName,
     PersonnelName,
     ClientName,
+    /// Category of a custom detector, by its upper-case name, so that it
+    /// reads back unchanged from reports; `from_placeholder_name` builds
+    /// it from any spelling
     Custom(String),
     DocumentNumber,
     RevisedBy,
@@ -63,8 +80,77 @@ pub enum Category {
     DesignedBy,
 }
 
+impl Category {
+    /// Upper-case name used inside placeholders, e.g. `EMAIL` in `[EMAIL_001]`
+    pub fn placeholder_name(&self) -> String {
+        match self {
+            Category::Email => "EMAIL".to_string(),
+            Category::Phone => "PHONE".to_string(),
+            Category::Iban => "IBAN".to_string(),
+            Category::NationalId => "NATIONAL_ID".to_string(),
+            Category::CreditCard => "CREDIT_CARD".to_string(),
+            Category::ProjectCode => "PROJECT_CODE".to_string(),
+            Category::ContractNumber => "CONTRACT_NUMBER".to_string(),
+            Category::WorkOrder => "WORK_ORDER".to_string(),
+            Category::PurchaseOrder => "PURCHASE_ORDER".to_string(),
+            Category::SerialNumber => "SERIAL_NUMBER".to_string(),
+            Category::CostCenter => "COST_CENTER".to_string(),
+            Category::CompanyName => "COMPANY_NAME".to_string(),
+            Category::ProjectName => "PROJECT_NAME".to_string(),
+            Category::PersonnelName => "PERSONNEL_NAME".to_string(),
+            Category::ClientName => "CLIENT_NAME".to_string(),
+            Category::Custom(s) => s.to_uppercase(),
+            Category::DocumentNumber => "DOCUMENT_NUMBER".to_string(),
+            Category::RevisedBy => "REVISED_BY".to_string(),
+            Category::ApprovedBy => "APPROVED_BY".to_string(),
+            Category::DesignedBy => "DESIGNED_BY".to_string(),
+        }
+    }
+
+    /// Category named by a placeholder name (case-insensitive); names of
+    /// no built-in category give an upper-case `Category::Custom`
+    pub fn from_placeholder_name(name: &str) -> Category {
+        match name.to_uppercase().as_str() {
+            "EMAIL" => Category::Email,
+            "PHONE" => Category::Phone,
+            "IBAN" => Category::Iban,
+            "NATIONAL_ID" => Category::NationalId,
+            "CREDIT_CARD" => Category::CreditCard,
+            "PROJECT_CODE" => Category::ProjectCode,
+            "CONTRACT_NUMBER" => Category::ContractNumber,
+            "WORK_ORDER" => Category::WorkOrder,
+            "PURCHASE_ORDER" => Category::PurchaseOrder,
+            "SERIAL_NUMBER" => Category::SerialNumber,
+            "COST_CENTER" => Category::CostCenter,
+            "COMPANY_NAME" => Category::CompanyName,
+            "PROJECT_NAME" => Category::ProjectName,
+            "PERSONNEL_NAME" => Category::PersonnelName,
+            "CLIENT_NAME" => Category::ClientName,
+            "DOCUMENT_NUMBER" => Category::DocumentNumber,
+            "REVISED_BY" => Category::RevisedBy,
+            "APPROVED_BY" => Category::ApprovedBy,
+            "DESIGNED_BY" => Category::DesignedBy,
+            other => Category::Custom(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for Category {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.placeholder_name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Category {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(Category::from_placeholder_name(&name))
+    }
+}
+
 /// Confidence level of a match
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Confidence {
     Verified,
     PatternOnly,
@@ -86,15 +172,129 @@ pub struct CandidateMatch {
     pub category: Category,
     pub priority: u32,
     pub confidence: Confidence,
+    /// Likelihood that the match is sensitive data, from 0.0 to 1.0,
+    /// reflecting pattern strength and checksum outcome
+    pub score: f64,
     pub raw_value: String,
     pub normalized_value: Option<String>,
 }
 
+impl CandidateMatch {
+    /// Value identifying the entity: the normalized form when the detector
+    /// provides one, the raw text otherwise
+    pub fn entity_value(&self) -> &str {
+        self.normalized_value.as_deref().unwrap_or(&self.raw_value)
+    }
+}
+
 /// Trait that all detectors must implement
 pub trait Detector: Send + Sync {
     fn id(&self) -> DetectorId;
     fn category(&self) -> Category;
     fn detect(&self, text: &str) -> Vec<CandidateMatch>;
+    /// Detect in normalized text, with the input it was produced from for
+    /// detectors that depend on its layout (line breaks). Spans refer to
+    /// the normalized text, as with `detect`.
+    fn detect_normalized(&self, normalized: &NormalizedText, _original: &str) -> Vec<CandidateMatch> {
+        self.detect(&normalized.content)
+    }
     fn validate(&self, candidate: &str) -> ValidationResult;
     fn priority(&self) -> u32;
 }
+
+/// Ids of the built-in detectors, in registration order
+pub const BUILTIN_DETECTORS: &[&str] = &[
+    // Personal data
+    "email",
+    "phone",
+    "spanish_id",
+    "iban",
+    "credit_card",
+    "ssn",
+    // Corporate/Industrial
+    "project_code",
+    "contract_number",
+    "work_order",
+    "purchase_order",
+    "serial_number",
+    "cost_center",
+    "document_number",
+    // Title-block sign-offs
+    "revised_by",
+    "approved_by",
+    "designed_by",
+];
+
+/// Instantiate a built-in detector by id
+pub fn builtin_detector(id: &str) -> Option<Box<dyn Detector>> {
+    let detector: Box<dyn Detector> = match id {
+        "email" => Box::new(EmailDetector::new()),
+        "phone" => Box::new(PhoneDetector::new()),
+        "spanish_id" => Box::new(SpanishIdDetector::new()),
+        "iban" => Box::new(IbanDetector::new()),
+        "credit_card" => Box::new(CreditCardDetector::new()),
+        "ssn" => Box::new(SsnDetector::new()),
+        "project_code" => Box::new(ProjectCodeDetector::new()),
+        "contract_number" => Box::new(ContractNumberDetector::new()),
+        "work_order" => Box::new(WorkOrderDetector::new()),
+        "purchase_order" => Box::new(PurchaseOrderDetector::new()),
+        "serial_number" => Box::new(SerialNumberDetector::new()),
+        "cost_center" => Box::new(CostCenterDetector::new()),
+        "document_number" => Box::new(DocumentNumberDetector::new()),
+        "revised_by" | "approved_by" | "designed_by" => Box::new(
+            LabelDetector::from_config(id, &LabelsConfig::default())
+                .expect("BUG: default sign-off labels are valid")
+                .expect("BUG: sign-off detector id is not a label detector"),
+        ),
+        _ => return None,
+    };
+    Some(detector)
+}
+
+/// Wraps a detector and replaces its priority with a configured value
+pub struct PriorityOverride {
+    inner: Box<dyn Detector>,
+    priority: u32,
+}
+
+impl PriorityOverride {
+    pub fn new(inner: Box<dyn Detector>, priority: u32) -> Self {
+        Self { inner, priority }
+    }
+
+    fn with_priority(&self, matches: Vec<CandidateMatch>) -> Vec<CandidateMatch> {
+        matches
+            .into_iter()
+            .map(|m| CandidateMatch {
+                priority: self.priority,
+                ..m
+            })
+            .collect()
+    }
+}
+
+impl Detector for PriorityOverride {
+    fn id(&self) -> DetectorId {
+        self.inner.id()
+    }
+
+    fn category(&self) -> Category {
+        self.inner.category()
+    }
+
+    fn detect(&self, text: &str) -> Vec<CandidateMatch> {
+        self.with_priority(self.inner.detect(text))
+    }
+
+    fn detect_normalized(&self, normalized: &NormalizedText, original: &str) -> Vec<CandidateMatch> {
+        self.with_priority(self.inner.detect_normalized(normalized, original))
+    }
+
+    fn validate(&self, candidate: &str) -> ValidationResult {
+        self.inner.validate(candidate)
+    }
+
+    fn priority(&self) -> u32 {
+        self.priority
+    }
+}
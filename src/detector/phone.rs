@@ -9,6 +9,8 @@ pub struct PhoneDetector {
 
 struct PhonePattern {
     regex: Regex,
+    /// Score of a bare digit run matched by this pattern
+    base_score: f64,
 }
 
 impl Default for PhoneDetector {
@@ -23,18 +25,22 @@ impl PhoneDetector {
             PhonePattern {
                 regex: Regex::new(r"(?:\+34[-\s]?)?[679][0-9]{2}[-\s]?[0-9]{3}[-\s]?[0-9]{3}")
                     .expect("BUG: Spanish phone regex is invalid"),
+                base_score: 0.5,
             },
             PhonePattern {
                 regex: Regex::new(r"(?:\+1[-\s]?)?\(?\d{3}\)?[-\s]?\d{3}[-\s]?\d{4}")
                     .expect("BUG: US phone regex is invalid"),
+                base_score: 0.3,
             },
             PhonePattern {
                 regex: Regex::new(r"(?:\+44[-\s]?)?[127][0-9]{3}[-\s]?[0-9]{6}")
                     .expect("BUG: UK phone regex is invalid"),
+                base_score: 0.35,
             },
             PhonePattern {
                 regex: Regex::new(r"\+[1-9]\d{1,14}")
                     .expect("BUG: E.164 phone regex is invalid"),
+                base_score: 0.8,
             },
         ];
         Self { patterns }
@@ -64,6 +70,7 @@ impl Detector for PhoneDetector {
                     category: Category::Phone,
                     priority: self.priority(),
                     confidence: Confidence::PatternOnly,
+                    score: phone_score(m.as_str(), pattern.base_score),
                     raw_value: m.as_str().to_string(),
                     normalized_value: Some(normalize_phone(m.as_str())),
                 })
@@ -87,3 +94,16 @@ fn normalize_phone(phone: &str) -> String {
         .filter(|c| c.is_ascii_digit() || *c == '+')
         .collect()
 }
+
+/// Formatting makes a digit run more likely to be a phone number: an
+/// international prefix (`+34 612 345 678`) scores high, grouping
+/// separators somewhat higher than a bare run such as `6123456789`
+fn phone_score(phone: &str, base_score: f64) -> f64 {
+    if phone.starts_with('+') {
+        0.9
+    } else if phone.contains(|c: char| c.is_whitespace() || c == '-' || c == '(') {
+        (base_score + 0.2).min(0.9)
+    } else {
+        base_score
+    }
+}
@@ -15,6 +15,14 @@ impl Default for SpanishIdDetector {
     }
 }
 
+/// Score of a DNI whose control letter is right. One in 23 arbitrary
+/// eight-digit codes followed by a letter passes the check.
+const DNI_SCORE: f64 = 0.95;
+
+/// Score of an NIE whose control letter is right; the X/Y/Z prefix makes
+/// a chance match rarer than for a DNI
+const NIE_SCORE: f64 = 0.98;
+
 impl SpanishIdDetector {
     pub fn new() -> Self {
         Self {
@@ -26,52 +34,15 @@ impl SpanishIdDetector {
     }
 }
 
-impl Detector for SpanishIdDetector {
-    fn id(&self) -> DetectorId {
-        "spanish_id".to_string()
-    }
-    
-    fn category(&self) -> Category {
-        Category::NationalId
-    }
-    
-    fn detect(&self, text: &str) -> Vec<CandidateMatch> {
-        let mut matches = Vec::new();
-        
-        for m in self.national_id_regex.find_iter(text) {
-            let raw = m.as_str();
-            let validation = self.validate(raw);
-            
-            let confidence = match validation {
-                ValidationResult::Valid => Confidence::Verified,
-                ValidationResult::Invalid => continue,
-                ValidationResult::NotApplicable => Confidence::PatternOnly,
-            };
-            
-            matches.push(CandidateMatch {
-                span: Span {
-                    start: m.start(),
-                    end: m.end(),
-                },
-                detector_id: self.id(),
-                category: self.category(),
-                priority: self.priority(),
-                confidence,
-                raw_value: raw.to_string(),
-                normalized_value: Some(normalize_spanish_id(raw)),
-            });
-        }
-        
-        for m in self.foreign_id_regex.find_iter(text) {
+impl SpanishIdDetector {
+    /// Matches of `regex` passing the control letter check; the others are
+    /// not IDs and are skipped
+    fn push_valid(&self, regex: &Regex, score: f64, text: &str, matches: &mut Vec<CandidateMatch>) {
+        for m in regex.find_iter(text) {
             let raw = m.as_str();
-            let validation = self.validate(raw);
-            
-            let confidence = match validation {
-                ValidationResult::Valid => Confidence::Verified,
-                ValidationResult::Invalid => continue,
-                ValidationResult::NotApplicable => Confidence::PatternOnly,
-            };
-            
+            if self.validate(raw) != ValidationResult::Valid {
+                continue;
+            }
             matches.push(CandidateMatch {
                 span: Span {
                     start: m.start(),
@@ -80,12 +51,28 @@ impl Detector for SpanishIdDetector {
                 detector_id: self.id(),
                 category: self.category(),
                 priority: self.priority(),
-                confidence,
+                confidence: Confidence::Verified,
+                score,
                 raw_value: raw.to_string(),
                 normalized_value: Some(normalize_spanish_id(raw)),
             });
         }
-        
+    }
+}
+
+impl Detector for SpanishIdDetector {
+    fn id(&self) -> DetectorId {
+        "spanish_id".to_string()
+    }
+    
+    fn category(&self) -> Category {
+        Category::NationalId
+    }
+    
+    fn detect(&self, text: &str) -> Vec<CandidateMatch> {
+        let mut matches = Vec::new();
+        self.push_valid(&self.national_id_regex, DNI_SCORE, text, &mut matches);
+        self.push_valid(&self.foreign_id_regex, NIE_SCORE, text, &mut matches);
         matches
     }
 
@@ -105,3 +92,22 @@ impl Detector for SpanishIdDetector {
 fn normalize_spanish_id(id: &str) -> String {
     id.to_uppercase()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_ids_with_a_valid_control_letter_are_found() {
+        let matches = SpanishIdDetector::new().detect("DNI 12345678Z, 12345678A, NIE X1234567L, Y1234567Z");
+        let found: Vec<_> = matches.iter().map(|m| (m.raw_value.as_str(), m.score)).collect();
+        assert_eq!(found, [("12345678Z", DNI_SCORE), ("X1234567L", NIE_SCORE)]);
+        assert!(matches.iter().all(|m| m.confidence == Confidence::Verified));
+        assert_eq!(matches[0].span, Span { start: 4, end: 13 });
+    }
+
+    #[test]
+    fn ids_inside_longer_codes_are_ignored() {
+        assert!(SpanishIdDetector::new().detect("REF912345678Z 123456789Z").is_empty());
+    }
+}
@@ -43,6 +43,7 @@ impl Detector for SsnDetector {
                 category: self.category(),
                 priority: self.priority(),
                 confidence: Confidence::PatternOnly,
+                score: 0.6,
                 raw_value: m.as_str().to_string(),
                 normalized_value: None,
             })
@@ -1,6 +1,6 @@
 // src/document_processor/docx.rs
 
-use crate::{Anonymizer, Result, AnonymizeError};
+use crate::{Anonymizer, AnonymizationSession, Result, AnonymizeError};
 use super::ProcessedDocument;
 use docx_rs::*;
 use std::io::Cursor;
@@ -10,6 +10,7 @@ pub fn process_docx(
     file_bytes: &[u8],
     original_filename: &str,
     anonymizer: &Anonymizer,
+    session: Option<&mut AnonymizationSession>,
 ) -> Result<ProcessedDocument> {
     // Read DOCX document (Updated API: accepts &[u8] directly)
     let docx = read_docx(file_bytes)
@@ -21,7 +22,10 @@ pub fn process_docx(
     let text = extract_text_from_docx(&docx);
 
     // Anonymize text with the existing engine
-    let output = anonymizer.anonymize(&text)?;
+    let output = match session {
+        Some(session) => anonymizer.anonymize_in_session(&text, session)?,
+        None => anonymizer.anonymize(&text)?,
+    };
 
     // Create new document with anonymized text
     let new_docx = create_anonymized_docx(&output.text);
@@ -45,6 +49,7 @@ pub fn process_docx(
         content_type: "application/vnd.openxmlformats-officedocument.wordprocessingml.document".to_string(),
         filename: new_filename,
         audit_report: output.report,
+        vault: output.vault,
     })
 }
 
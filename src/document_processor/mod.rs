@@ -2,7 +2,7 @@
 
 pub mod docx;
 
-use crate::{Result, AuditReport};
+use crate::{Result, AuditReport, MappingVault};
 
 /// Detected document type
 #[derive(Debug, Clone, Copy)]
@@ -28,13 +28,18 @@ pub struct ProcessedDocument {
     pub content_type: String,
     pub filename: String,
     pub audit_report: AuditReport,
+    pub vault: MappingVault,
 }
 
-/// Process document according to type
+/// Process document according to type.
+///
+/// When a session is given, placeholders are shared with the other
+/// documents of that session.
 pub fn process_document(
     file_bytes: &[u8],
     filename: &str,
     anonymizer: &crate::Anonymizer,
+    session: Option<&mut crate::AnonymizationSession>,
 ) -> Result<ProcessedDocument> {
     let doc_type = DocumentType::from_filename(filename)
         .ok_or_else(|| crate::AnonymizeError::ConfigError {
@@ -42,6 +47,6 @@ pub fn process_document(
         })?;
 
     match doc_type {
-        DocumentType::Docx => docx::process_docx(file_bytes, filename, anonymizer),
+        DocumentType::Docx => docx::process_docx(file_bytes, filename, anonymizer, session),
     }
 }
\ No newline at end of file
@@ -1,23 +1,65 @@
 // src/engine.rs
 
-use crate::Result;
-use crate::normalizer::normalize;
-use crate::detector::Detector;
-use crate::conflict_resolver::ConflictResolver;
-use crate::replacement_engine::ReplacementEngine;
-use crate::audit_report::{AuditReport, ContentHash};
+use crate::{AnonymizeError, Result};
+use crate::config::AnonymizeConfig;
+use crate::normalizer::{normalize_with_limit, DEFAULT_MAX_INPUT_SIZE};
+use crate::detector::{
+    CandidateMatch, Category, Detector, DetectorId, PriorityOverride, RegexDetector, GazetteerDetector, LabelDetector,
+    DocumentNumberDetector, BUILTIN_DETECTORS, builtin_detector,
+};
+use crate::conflict_resolver::{ConflictResolver, ConflictStrategy};
+use crate::policy::{Action, Policy};
+use crate::replacement_engine::{ReplacementEngine, PlaceholderTable};
+use crate::session::AnonymizationSession;
+use crate::scan::{Finding, ScanReport};
+use crate::context::ContextAnalyzer;
+use crate::value_lists::{ListVerdict, ValueLists, DENYLIST_DETECTOR_ID};
+use crate::audit_report::{
+    AuditDisclosure, AuditReport, ConflictRecord, ContentHash, DetectorStatistics, ReplacementRecord,
+    StageTimings,
+};
+use crate::vault::MappingVault;
+use crate::signing::ReportSigner;
+use crate::ledger::Ledger;
+use crate::report_format::REPORT_VERSION;
 use sha2::{Sha256, Digest};
 use std::time::Instant;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 pub struct AnonymizationOutput {
     pub text: String,
     pub report: AuditReport,
     pub hash: ContentHash,
+    /// Placeholder → original mappings, to be sealed for later re-identification
+    pub vault: MappingVault,
+}
+
+/// Candidates found in a text, before conflict resolution
+struct Detection {
+    candidates: Vec<CandidateMatch>,
+    timings: StageTimings,
+    detectors: BTreeMap<String, DetectorStatistics>,
+    suppressed_by_allowlist: usize,
+    discarded_by_context: usize,
+    below_threshold: usize,
 }
 
 pub struct Anonymizer {
     detectors: Vec<Box<dyn Detector>>,
+    context: ContextAnalyzer,
+    lists: ValueLists,
+    resolver: ConflictResolver,
+    policy: Policy,
+    replacer: ReplacementEngine,
+    disclosure: AuditDisclosure,
+    signer: Option<ReportSigner>,
+    ledger: Option<Ledger>,
+    max_input_size: usize,
+    /// Minimum score by detector id
+    min_scores: HashMap<DetectorId, f64>,
+    /// Hash of the configuration that built this engine; engines
+    /// assembled by hand with `add_detector` report `"manual"`
+    config_hash: String,
 }
 
 impl Default for Anonymizer {
@@ -30,76 +72,556 @@ impl Anonymizer {
     pub fn new() -> Self {
         Self {
             detectors: Vec::new(),
+            context: ContextAnalyzer::default(),
+            lists: ValueLists::default(),
+            resolver: ConflictResolver::default(),
+            policy: Policy::default(),
+            replacer: ReplacementEngine::new(),
+            disclosure: AuditDisclosure::default(),
+            signer: None,
+            ledger: None,
+            max_input_size: DEFAULT_MAX_INPUT_SIZE,
+            min_scores: HashMap::new(),
+            config_hash: "manual".to_string(),
+        }
+    }
+
+    /// Build an engine from a configuration.
+    ///
+    /// Built-in detectors are enabled unless switched off in
+    /// `[detectors.<id>]`, and take the configured priority if one is set.
+    /// Detectors from `[[custom_detectors]]` and `[gazetteers]` follow.
+    pub fn from_config(config: &AnonymizeConfig) -> Result<Self> {
+        let mut configured: Vec<Box<dyn Detector>> = Vec::new();
+        for custom in &config.custom_detectors {
+            configured.push(Box::new(RegexDetector::from_config(custom)?));
+        }
+        for (category, gazetteer) in &config.gazetteers {
+            configured.push(Box::new(GazetteerDetector::from_config(category, gazetteer)?));
+        }
+
+        let mut ids: Vec<String> = BUILTIN_DETECTORS.iter().map(|id| id.to_string()).collect();
+        for detector in &configured {
+            let id = detector.id();
+            if id == DENYLIST_DETECTOR_ID {
+                return Err(AnonymizeError::ConfigError {
+                    message: format!("Detector id '{}' is reserved for denylist matches", id),
+                });
+            }
+            if ids.contains(&id) {
+                return Err(AnonymizeError::ConfigError {
+                    message: format!("Duplicate detector id '{}'", id),
+                });
+            }
+            ids.push(id);
         }
+
+        if let Some(unknown) = config.detectors.keys().find(|id| !ids.contains(id)) {
+            return Err(AnonymizeError::ConfigError {
+                message: format!(
+                    "Unknown detector '{}' (available: {})",
+                    unknown,
+                    ids.join(", ")
+                ),
+            });
+        }
+
+        let mut builtins: Vec<Box<dyn Detector>> = Vec::new();
+        for id in BUILTIN_DETECTORS {
+            builtins.push(configured_builtin(id, config)?);
+        }
+        let categories: Vec<(String, Category)> = builtins.iter()
+            .chain(&configured)
+            .map(|d| (d.id(), d.category()))
+            .collect();
+
+        let policy = Policy::from_config(&config.policy);
+        let replacer = ReplacementEngine::from_config(config, &policy)?;
+
+        let mut engine = Self {
+            detectors: Vec::new(),
+            context: ContextAnalyzer::from_config(&config.context)?,
+            lists: ValueLists::from_config(&config.lists, &categories)?,
+            resolver: ConflictResolver::new(config.conflicts.strategy.build()),
+            policy,
+            replacer,
+            disclosure: AuditDisclosure::from_config(&config.audit, &config.replacement.mask)?,
+            signer: ReportSigner::from_config(&config.audit.signing)?,
+            ledger: Ledger::from_config(&config.ledger)?,
+            max_input_size: config.limits.max_input_size,
+            min_scores: HashMap::new(),
+            config_hash: config.hash(),
+        };
+
+        for detector in builtins.into_iter().chain(configured) {
+            let settings = config.detector(&detector.id());
+            if !settings.enabled {
+                continue;
+            }
+            if let Some(min_score) = settings.min_score {
+                engine.set_min_score(&detector.id(), min_score)?;
+            }
+
+            match settings.priority {
+                Some(priority) => engine.add_detector(Box::new(PriorityOverride::new(detector, priority))),
+                None => engine.add_detector(detector),
+            }
+        }
+
+        Ok(engine)
+    }
+
+    /// Replace the keyword lists used to adjust scores from context
+    pub fn set_context_analyzer(&mut self, context: ContextAnalyzer) {
+        self.context = context;
+    }
+
+    /// Values never (allowlist) or always (denylist) anonymized
+    pub fn set_value_lists(&mut self, lists: ValueLists) {
+        self.lists = lists;
+    }
+
+    /// Choose how overlapping matches are resolved (leftmost-longest by
+    /// default)
+    pub fn set_conflict_strategy(&mut self, strategy: Box<dyn ConflictStrategy>) {
+        self.resolver = ConflictResolver::new(strategy);
+    }
+
+    /// Choose the action applied to each category (redact by default)
+    pub fn set_policy(&mut self, policy: Policy) {
+        self.policy = policy;
+    }
+
+    /// Replacement machinery, e.g. to install the HMAC key ring needed by
+    /// the `hash` action on a hand-built engine
+    pub fn replacer_mut(&mut self) -> &mut ReplacementEngine {
+        &mut self.replacer
+    }
+
+    /// Choose what audit reports reveal of the original values (nothing
+    /// by default)
+    pub fn set_disclosure(&mut self, disclosure: AuditDisclosure) {
+        self.disclosure = disclosure;
+    }
+
+    /// Sign every audit report with this key ring's active key
+    pub fn set_signer(&mut self, signer: ReportSigner) {
+        self.signer = Some(signer);
+    }
+
+    /// Record every anonymization in a hash-chained ledger
+    pub fn set_ledger(&mut self, ledger: Ledger) {
+        self.ledger = Some(ledger);
+    }
+
+    pub fn ledger(&self) -> Option<&Ledger> {
+        self.ledger.as_ref()
+    }
+
+    /// Hash of the configuration recorded in audit reports
+    pub fn config_hash(&self) -> &str {
+        &self.config_hash
     }
 
     pub fn add_detector(&mut self, detector: Box<dyn Detector>) {
         self.detectors.push(detector);
     }
 
+    /// Drop the candidates of a detector scoring below `min_score`
+    /// (0.0 to 1.0), trading recall for precision
+    pub fn set_min_score(&mut self, detector_id: &str, min_score: f64) -> Result<()> {
+        if !(0.0..=1.0).contains(&min_score) {
+            return Err(AnonymizeError::ConfigError {
+                message: format!(
+                    "min_score of detector '{}' must be between 0 and 1, got {}",
+                    detector_id, min_score
+                ),
+            });
+        }
+        self.min_scores.insert(detector_id.to_string(), min_score);
+        Ok(())
+    }
+
     pub fn anonymize(&self, text: &str) -> Result<AnonymizationOutput> {
+        self.anonymize_with_table(text, &mut PlaceholderTable::new(), None)
+    }
+
+    /// Anonymize a text within a session, reusing the placeholders already
+    /// assigned to values seen in earlier documents of the session
+    pub fn anonymize_in_session(
+        &self,
+        text: &str,
+        session: &mut AnonymizationSession,
+    ) -> Result<AnonymizationOutput> {
+        let session_id = session.id().to_string();
+        let output = self.anonymize_with_table(text, session.table_mut(), Some(session_id))?;
+        session.record_document();
+        Ok(output)
+    }
+
+    /// Detect sensitive data without anonymizing it.
+    ///
+    /// Runs normalization, every detector and conflict resolution, and
+    /// reports what was found; no replacement is computed. With
+    /// `include_discarded`, the candidates that lost a conflict are
+    /// listed as well.
+    pub fn scan(&self, text: &str, include_discarded: bool) -> Result<ScanReport> {
         let start_time = Instant::now();
-        
-        // 1. Normalize
-        let normalized = normalize(text)?;
-        
-        // 2. Detection
-        let mut all_candidates = Vec::new();
+
+        let detection = self.detect(text)?;
+        let mut timings = detection.timings;
+        let mut detectors = detection.detectors;
+
+        let mut stage = Instant::now();
+        let resolution = self.resolver.resolve(text, detection.candidates);
+        timings.conflict_resolution_us = micros(&mut stage);
+        for m in &resolution.accepted {
+            detectors.entry(m.detector_id.clone()).or_default().accepted += 1;
+        }
+
+        let discarded = include_discarded.then(|| {
+            let mut discarded: Vec<ConflictRecord> = resolution.discarded.iter().map(ConflictRecord::from).collect();
+            discarded.sort_by_key(|c| (c.discarded.span.start, c.discarded.span.end));
+            discarded
+        });
+
+        Ok(ScanReport {
+            timestamp: chrono::Utc::now(),
+            input_hash: format!("{:x}", Sha256::digest(text.as_bytes())),
+            config_hash: self.config_hash.clone(),
+            findings: resolution.accepted.iter().map(Finding::from).collect(),
+            discarded,
+            suppressed_by_allowlist: detection.suppressed_by_allowlist,
+            discarded_by_context: detection.discarded_by_context,
+            below_threshold: detection.below_threshold,
+            processing_time_ms: start_time.elapsed().as_millis() as u64,
+            timings,
+            detectors,
+        })
+    }
+
+    /// Normalize the text and run every detector, returning candidates
+    /// located in the original text
+    fn detect(&self, text: &str) -> Result<Detection> {
+        let mut timings = StageTimings::default();
+        let mut stage = Instant::now();
+        let normalized = normalize_with_limit(text, self.max_input_size)?;
+        timings.normalization_us = micros(&mut stage);
+
+        let mut all_candidates: Vec<CandidateMatch> = Vec::new();
+        let mut detectors = BTreeMap::new();
         for detector in &self.detectors {
-            let matches = detector.detect(&normalized.content);
+            let started = Instant::now();
+            let matches = detector.detect_normalized(&normalized, text);
+            detectors.insert(detector.id(), DetectorStatistics {
+                candidates: matches.len(),
+                accepted: 0,
+                duration_us: started.elapsed().as_micros() as u64,
+            });
             all_candidates.extend(matches);
         }
+
+        // Denylisted values are anonymized even where no detector finds them
+        let mut denylisted = Vec::new();
+        if self.lists.has_denylist() {
+            let started = Instant::now();
+            denylisted = self.lists.find_denied(&normalized.content);
+            detectors.insert(DENYLIST_DETECTOR_ID.to_string(), DetectorStatistics {
+                candidates: denylisted.len(),
+                accepted: 0,
+                duration_us: started.elapsed().as_micros() as u64,
+            });
+        }
+
+        // Detectors see the normalized text; map their spans back so the
+        // original layout (line breaks, indentation) survives replacement.
+        // The normalized value keeps placeholders stable across layouts.
+        for candidate in all_candidates.iter_mut().chain(&mut denylisted) {
+            let span = normalized.offsets.to_original(candidate.span);
+            let original = text[span.start..span.end].to_string();
+            let normalized_value = std::mem::replace(&mut candidate.raw_value, original);
+            candidate.normalized_value.get_or_insert(normalized_value);
+            candidate.span = span;
+        }
+        timings.detection_us = micros(&mut stage);
+
+        // Allowlisted values are dropped, along with partial matches
+        // inside them; denylisted ones are kept whatever their context
+        // and score
+        let mut denied = Vec::new();
+        let mut unlisted = Vec::new();
+        let mut allowed_spans = Vec::new();
+        for candidate in all_candidates {
+            match self.lists.verdict(&candidate) {
+                ListVerdict::Allowed => allowed_spans.push(candidate.span),
+                ListVerdict::Denied => denied.push(CandidateMatch { score: 1.0, ..candidate }),
+                ListVerdict::Unlisted => unlisted.push(candidate),
+            }
+        }
+        // Keep the detector's match where one also found the value
+        denylisted.retain(|d| !denied.iter().any(|c: &CandidateMatch| c.span == d.span));
+        denied.extend(denylisted);
+        let listed = unlisted.len() + allowed_spans.len();
+        unlisted.retain(|c| {
+            !allowed_spans.iter().any(|a| a.start <= c.span.start && c.span.end <= a.end)
+        });
+        let suppressed_by_allowlist = listed - unlisted.len();
+
+        // Raise or lower scores from the surrounding words
+        let (mut all_candidates, discarded_by_context) = self.context.apply(text, unlisted);
+
+        let before = all_candidates.len();
+        all_candidates.retain(|c| {
+            self.min_scores.get(&c.detector_id).is_none_or(|min| c.score >= *min)
+        });
+        let below_threshold = before - all_candidates.len();
+        all_candidates.extend(denied);
+        timings.filtering_us = micros(&mut stage);
+
+        Ok(Detection {
+            timings,
+            detectors,
+            suppressed_by_allowlist,
+            discarded_by_context,
+            below_threshold,
+            candidates: all_candidates,
+        })
+    }
+
+    fn anonymize_with_table(
+        &self,
+        text: &str,
+        table: &mut PlaceholderTable,
+        session_id: Option<String>,
+    ) -> Result<AnonymizationOutput> {
+        let start_time = Instant::now();
         
-        let initial_match_count = all_candidates.len();
+        // 1-2. Normalization and detection
+        let detection = self.detect(text)?;
         
+        let mut timings = detection.timings;
+        let mut detectors = detection.detectors;
+
         // 3. Conflict Resolution
-        let resolved_matches = ConflictResolver::resolve(all_candidates);
+        let mut stage = Instant::now();
+        let resolution = self.resolver.resolve(text, detection.candidates);
+        timings.conflict_resolution_us = micros(&mut stage);
+        let mut conflicts: Vec<ConflictRecord> = resolution.discarded.iter().map(ConflictRecord::from).collect();
+        conflicts.sort_by_key(|c| (c.discarded.span.start, c.discarded.span.end));
+        let resolved_matches = resolution.accepted;
         let final_match_count = resolved_matches.len();
-        let conflicts_resolved = initial_match_count - final_match_count;
+        let conflicts_resolved = conflicts.len();
         
-        // 4. Replacement
-        let replacement_result = ReplacementEngine::replace(&normalized.content, resolved_matches);
+        // 4. Policy: choose an action for each match
+        let planned = self.policy.plan(resolved_matches);
+        timings.policy_us = micros(&mut stage);
+
+        // 5. Replacement
+        let replacement_result = self.replacer.replace(text, planned, table)?;
+        timings.replacement_us = micros(&mut stage);
+        for r in &replacement_result.replacements {
+            detectors.entry(r.detector_id.clone()).or_default().accepted += 1;
+        }
         
-        // 5. Output Building & Audit
+        // 6. Output Building & Audit
         let input_hash_val = format!("{:x}", Sha256::digest(text.as_bytes()));
         let output_hash_val = format!("{:x}", Sha256::digest(replacement_result.anonymized_text.as_bytes()));
 
-        let mut matches_by_category = HashMap::new();
+        let mut matches_by_category = BTreeMap::new();
         for r in &replacement_result.replacements {
-            let cat_str = format!("{:?}", r.category);
-            *matches_by_category.entry(cat_str).or_insert(0) += 1;
+            *matches_by_category.entry(r.category.placeholder_name()).or_insert(0) += 1;
+        }
+        
+        // One record per entity and output, listing every occurrence in
+        // order. Outputs alone do not identify entities: masks of distinct
+        // values often coincide. The vault gets the first spelling of each
+        // entity.
+        let mut vault = MappingVault::new();
+        let mut records: Vec<ReplacementRecord> = Vec::new();
+        let mut record_index: HashMap<(Action, String, String, String), usize> = HashMap::new();
+        for r in replacement_result.replacements {
+            let kept = r.action == Action::Keep;
+            let key = (r.action, r.category.placeholder_name(), r.entity_value.clone(), r.placeholder.clone());
+            match record_index.get(&key) {
+                Some(&i) => {
+                    records[i].occurrences.push(r.span);
+                    records[i].score = records[i].score.max(r.score);
+                }
+                None => {
+                    if r.action.is_reversible() {
+                        vault.insert(r.placeholder.clone(), r.original.clone())?;
+                    }
+                    record_index.insert(key, records.len());
+                    records.push(ReplacementRecord {
+                        // A kept value's "placeholder" is the value itself
+                        placeholder: (!kept).then_some(r.placeholder),
+                        category: r.category.clone(),
+                        detector_id: r.detector_id,
+                        confidence: r.confidence,
+                        score: r.score,
+                        occurrences: vec![r.span],
+                        original_value: self.disclosure.original_value(&r.category, &r.original),
+                        original_hash: self.disclosure.original_hash(&r.category, &r.original),
+                        action: r.action,
+                        key_id: r.key_id,
+                    });
+                }
+            }
         }
         
-        let report = AuditReport {
-            version: "0.1.0".to_string(),
+        let hash = ContentHash {
+            algorithm: "SHA-256".to_string(),
+            value: output_hash_val,
+        };
+
+        let mut report = AuditReport {
+            version: REPORT_VERSION.to_string(),
             timestamp: chrono::Utc::now(),
             input_hash: input_hash_val,
-            config_hash: "default".to_string(),
+            config_hash: self.config_hash.clone(),
+            output_hash: Some(hash.clone()),
+            disclosure: self.disclosure.disclosure(),
+            session_id,
             statistics: crate::audit_report::Statistics {
                 total_matches: final_match_count,
                 matches_by_category,
                 conflicts_resolved,
+                suppressed_by_allowlist: detection.suppressed_by_allowlist,
+                discarded_by_context: detection.discarded_by_context,
+                below_threshold: detection.below_threshold,
                 processing_time_ms: start_time.elapsed().as_millis() as u64,
+                timings,
+                detectors,
             },
-            replacements: replacement_result.replacements.into_iter().map(|r| {
-                crate::audit_report::ReplacementRecord {
-                    placeholder: r.placeholder,
-                    category: format!("{:?}", r.category),
-                    detector_id: r.detector_id,
-                    confidence: format!("{:?}", r.confidence),
-                    original_span: r.span,
-                    original_value: Some(r.original),
-                }
-            }).collect(),
+            replacements: records,
+            conflicts,
+            signature: None,
         };
+        if let Some(signer) = &self.signer {
+            signer.sign(&mut report);
+        }
+        // A run that cannot be recorded is not returned
+        if let Some(ledger) = &self.ledger {
+            ledger.append(&report)?;
+        }
 
         Ok(AnonymizationOutput {
             text: replacement_result.anonymized_text,
             report,
-            hash: ContentHash {
-                algorithm: "SHA-256".to_string(),
-                value: output_hash_val,
-            },
+            hash,
+            vault,
         })
     }
 }
+
+/// Microseconds since `stage`, which is reset to now for the next stage
+fn micros(stage: &mut Instant) -> u64 {
+    let elapsed = stage.elapsed().as_micros() as u64;
+    *stage = Instant::now();
+    elapsed
+}
+
+/// Built-in detector with the settings of its configuration section
+fn configured_builtin(id: &str, config: &AnonymizeConfig) -> Result<Box<dyn Detector>> {
+    if let Some(detector) = LabelDetector::from_config(id, &config.labels)? {
+        return Ok(Box::new(detector));
+    }
+    if id == "document_number" && !config.document_numbers.is_empty() {
+        return Ok(Box::new(DocumentNumberDetector::from_templates(&config.document_numbers)?));
+    }
+    Ok(builtin_detector(id).expect("BUG: built-in detector id is not registered"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::records_csv;
+
+    const TEXT: &str = "Write to john@example.com, pay ES91 2100 0418 4502 0005 1332, john@example.com";
+
+    fn anonymizer(toml: &str) -> Anonymizer {
+        Anonymizer::from_config(&AnonymizeConfig::from_toml_str(toml).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn merged_parts_count_as_resolved_conflicts() {
+        let output = anonymizer(
+            "[conflicts]\nstrategy = \"merge_adjacent\"\n[gazetteers.CLIENT_NAME]\nterms = [\"Acme\", \"Iberia\", \"Globex\"]\n",
+        )
+        .anonymize("Contrato Acme Iberia y Globex")
+        .unwrap();
+        assert_eq!(output.text, "Contrato [CLIENT_NAME_001] y [CLIENT_NAME_002]");
+
+        let statistics = &output.report.statistics;
+        assert_eq!(statistics.total_matches, 2);
+        assert_eq!(statistics.conflicts_resolved, 2);
+        assert_eq!(statistics.conflicts_resolved, output.report.conflicts.len());
+    }
+
+    #[test]
+    fn denylist_id_is_reserved() {
+        let config = AnonymizeConfig::from_toml_str(
+            "[[custom_detectors]]\nid = \"denylist\"\npattern = \"ZX-[0-9]+\"\ncategory = \"LOT_CODE\"\n",
+        ).unwrap();
+        let Err(AnonymizeError::ConfigError { message }) = Anonymizer::from_config(&config) else {
+            panic!("a custom detector named denylist was accepted");
+        };
+        assert!(message.contains("reserved"), "{}", message);
+    }
+
+    #[test]
+    fn kept_values_stay_out_of_reports_by_default() {
+        let output = anonymizer("[policy.categories]\nEMAIL = \"keep\"\nIBAN = \"keep\"\n")
+            .anonymize(TEXT)
+            .unwrap();
+        assert_eq!(output.text, TEXT);
+
+        let report = &output.report;
+        assert_eq!(report.disclosure, crate::Disclosure::None);
+        assert_eq!(report.replacements.len(), 2);
+        assert_eq!(report.replacements[0].occurrences.len(), 2);
+        assert!(report.replacements.iter().all(|r| r.placeholder.is_none() && r.original_value.is_none()));
+
+        let json = serde_json::to_string(report).unwrap();
+        let csv = records_csv(report);
+        for raw in ["john@example.com", "ES91 2100 0418 4502 0005 1332", "ES9121000418450200051332"] {
+            assert!(!json.contains(raw), "{} in report", raw);
+            assert!(!csv.contains(raw), "{} in CSV", raw);
+        }
+    }
+
+    #[test]
+    fn values_masking_alike_keep_separate_records() {
+        let output = anonymizer("[policy.categories]\nIBAN = \"mask\"\n[audit]\ndisclosure = \"full\"\n")
+            .anonymize("From ES91 2100 0418 4502 0005 1332, to ES09 2080 0418 4502 0005 1332, refund ES91 2100 0418 4502 0005 1332")
+            .unwrap();
+        assert_eq!(output.text.matches("ES** **** **** **** **** 1332").count(), 3);
+
+        let records: Vec<_> = output.report.replacements.iter()
+            .map(|r| (r.placeholder.as_deref().unwrap(), r.original_value.as_deref().unwrap(), r.occurrences.len()))
+            .collect();
+        assert_eq!(records, [
+            ("ES** **** **** **** **** 1332", "ES91 2100 0418 4502 0005 1332", 2),
+            ("ES** **** **** **** **** 1332", "ES09 2080 0418 4502 0005 1332", 1),
+        ]);
+    }
+
+    #[test]
+    fn denylisted_values_are_anonymized_without_a_detector() {
+        let output = anonymizer("[lists.categories.PROJECT_NAME]\ndeny = [\"Proyecto Halcón\"]\n")
+            .anonymize("Informe del proyecto halcon, fase 2")
+            .unwrap();
+        assert_eq!(output.text, "Informe del [PROJECT_NAME_001], fase 2");
+        assert_eq!(output.report.statistics.detectors[DENYLIST_DETECTOR_ID].accepted, 1);
+    }
+
+    #[test]
+    fn kept_values_follow_the_disclosure_level() {
+        let output = anonymizer("[policy.categories]\nEMAIL = \"keep\"\n[audit]\ndisclosure = \"masked\"\n")
+            .anonymize(TEXT)
+            .unwrap();
+        let record = &output.report.replacements[0];
+        assert_eq!(record.placeholder, None);
+        assert_eq!(record.original_value.as_deref(), Some("****@example.com"));
+    }
+}
@@ -31,6 +31,24 @@ pub enum AnonymizeError {
         message: String,
     },
 
+    /// Mapping vault could not be sealed or opened
+    #[error("Vault error: {message}")]
+    VaultError {
+        message: String,
+    },
+
+    /// Audit ledger is unreadable or its chain is broken
+    #[error("Ledger error: {message}")]
+    LedgerError {
+        message: String,
+    },
+
+    /// Audit report of an unknown version or malformed
+    #[error("Report error: {message}")]
+    ReportError {
+        message: String,
+    },
+
     /// IO error (file read/write)
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
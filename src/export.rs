@@ -0,0 +1,385 @@
+// src/export.rs
+
+use serde::{Serialize, Deserialize};
+use std::fmt::Write;
+use crate::audit_report::{AuditReport, ReplacementRecord};
+use crate::detector::Category;
+use crate::policy::Action;
+
+/// Renderings of an audit report for readers without JSON tooling
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// One row per replacement record (`records_csv`)
+    Csv,
+    /// One row per statistic (`statistics_csv`)
+    StatisticsCsv,
+    /// Self-contained page with the highlighted text (`report_html`)
+    Html,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv | ExportFormat::StatisticsCsv => "text/csv; charset=utf-8",
+            ExportFormat::Html => "text/html; charset=utf-8",
+        }
+    }
+
+    /// Suggested file name of the download
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "audit-records.csv",
+            ExportFormat::StatisticsCsv => "audit-statistics.csv",
+            ExportFormat::Html => "audit-report.html",
+        }
+    }
+}
+
+/// Replacement records as CSV, one row per placeholder. Kept values have
+/// no placeholder.
+///
+/// Spans are `start-end` byte offsets of the original text, separated by
+/// `;`. The original value or its hash is filled in only as far as the
+/// report's disclosure level allows.
+pub fn records_csv(report: &AuditReport) -> String {
+    let mut csv = String::from(
+        "placeholder,category,detector_id,confidence,score,action,key_id,occurrences,spans,original_value,original_hash\n",
+    );
+    for r in &report.replacements {
+        let spans: Vec<String> = r.occurrences.iter().map(|s| format!("{}-{}", s.start, s.end)).collect();
+        let row = [
+            r.placeholder.clone().unwrap_or_default(),
+            r.category.placeholder_name(),
+            r.detector_id.clone(),
+            serde_name(&r.confidence),
+            r.score.to_string(),
+            serde_name(&r.action),
+            r.key_id.clone().unwrap_or_default(),
+            r.occurrences.len().to_string(),
+            spans.join(";"),
+            r.original_value.clone().unwrap_or_default(),
+            r.original_hash.clone().unwrap_or_default(),
+        ];
+        push_row(&mut csv, &row);
+    }
+    csv
+}
+
+/// Report header and statistics as `metric,value` CSV rows; match counts
+/// appear as `matches_by_category.<category>`
+pub fn statistics_csv(report: &AuditReport) -> String {
+    let stats = &report.statistics;
+    let mut rows: Vec<(String, String)> = vec![
+        ("timestamp".to_string(), report.timestamp.to_rfc3339()),
+        ("input_hash".to_string(), report.input_hash.clone()),
+        ("config_hash".to_string(), report.config_hash.clone()),
+        ("output_hash".to_string(), report.output_hash.as_ref().map(|h| h.value.clone()).unwrap_or_default()),
+        ("disclosure".to_string(), serde_name(&report.disclosure)),
+        ("total_matches".to_string(), stats.total_matches.to_string()),
+        ("conflicts_resolved".to_string(), stats.conflicts_resolved.to_string()),
+        ("suppressed_by_allowlist".to_string(), stats.suppressed_by_allowlist.to_string()),
+        ("discarded_by_context".to_string(), stats.discarded_by_context.to_string()),
+        ("below_threshold".to_string(), stats.below_threshold.to_string()),
+        ("processing_time_ms".to_string(), stats.processing_time_ms.to_string()),
+    ];
+    for (category, count) in &stats.matches_by_category {
+        rows.push((format!("matches_by_category.{}", category), count.to_string()));
+    }
+    for (stage, micros) in stats.timings.stages() {
+        rows.push((format!("timings.{}_us", stage), micros.to_string()));
+    }
+    for (id, detector) in &stats.detectors {
+        rows.push((format!("detectors.{}.candidates", id), detector.candidates.to_string()));
+        rows.push((format!("detectors.{}.accepted", id), detector.accepted.to_string()));
+        rows.push((format!("detectors.{}.duration_us", id), detector.duration_us.to_string()));
+    }
+
+    let mut csv = String::from("metric,value\n");
+    for (metric, value) in rows {
+        push_row(&mut csv, &[metric, value]);
+    }
+    csv
+}
+
+/// A self-contained HTML page for reviewers: report summary, the
+/// anonymized text with every replacement highlighted in its category's
+/// color and linked to its record, and the table of records.
+///
+/// `anonymized_text` must be the text the report was produced with;
+/// replacements that cannot be located in it are left unhighlighted.
+pub fn report_html(report: &AuditReport, anonymized_text: &str) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>Anonymization audit report</title>\n<style>\n");
+    html.push_str(STYLE);
+    for category in categories(report) {
+        let _ = writeln!(
+            html,
+            ".cat-{} {{ background: hsl({}, 70%, 85%); }}",
+            css_class(&category),
+            hue(&category)
+        );
+    }
+    html.push_str("</style>\n</head>\n<body>\n<h1>Anonymization audit report</h1>\n");
+
+    // Summary
+    let stats = &report.statistics;
+    html.push_str("<table class=\"summary\">\n");
+    let mut summary = vec![
+        ("Date", report.timestamp.to_rfc3339()),
+        ("Input SHA-256", report.input_hash.clone()),
+        ("Output SHA-256", report.output_hash.as_ref().map(|h| h.value.clone()).unwrap_or_default()),
+        ("Configuration", report.config_hash.clone()),
+        ("Original values disclosed", serde_name(&report.disclosure)),
+        ("Values replaced", stats.total_matches.to_string()),
+        ("Overlaps resolved", stats.conflicts_resolved.to_string()),
+        ("Allowlisted", stats.suppressed_by_allowlist.to_string()),
+        ("Dropped by context", stats.discarded_by_context.to_string()),
+        ("Below threshold", stats.below_threshold.to_string()),
+    ];
+    if let Some(session_id) = &report.session_id {
+        summary.push(("Session", session_id.clone()));
+    }
+    if let Some(signature) = &report.signature {
+        summary.push(("Signed", format!("{} (key {})", signature.algorithm, signature.key_id)));
+    }
+    for (label, value) in summary {
+        let _ = writeln!(html, "<tr><th>{}</th><td>{}</td></tr>", label, escape(&value));
+    }
+    html.push_str("</table>\n");
+
+    // Text
+    html.push_str("<h2>Anonymized text</h2>\n<pre class=\"text\">");
+    let mut position = 0;
+    for (start, end, index) in locate(report, anonymized_text) {
+        let record = &report.replacements[index];
+        html.push_str(&escape(&anonymized_text[position..start]));
+        let _ = write!(
+            html,
+            "<a class=\"ph cat-{}\" href=\"#record-{}\" title=\"{}\">{}</a>",
+            css_class(&record.category),
+            index + 1,
+            escape(&record.category.placeholder_name()),
+            escape(&anonymized_text[start..end])
+        );
+        position = end;
+    }
+    html.push_str(&escape(&anonymized_text[position..]));
+    html.push_str("</pre>\n");
+
+    // Records
+    html.push_str("<h2>Replacements</h2>\n<table class=\"records\">\n");
+    html.push_str("<tr><th>#</th><th>Replacement</th><th>Category</th><th>Detector</th><th>Confidence</th><th>Score</th><th>Action</th><th>Occurrences</th><th>Original</th></tr>\n");
+    for (i, r) in report.replacements.iter().enumerate() {
+        let original = r.original_value.clone()
+            .or_else(|| r.original_hash.as_ref().map(|h| format!("sha256:{}", h)))
+            .unwrap_or_else(|| "—".to_string());
+        let _ = writeln!(
+            html,
+            "<tr id=\"record-{n}\"><td>{n}</td><td><span class=\"ph cat-{class}\">{}</span></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape(r.placeholder.as_deref().unwrap_or("(kept)")),
+            escape(&r.category.placeholder_name()),
+            escape(&r.detector_id),
+            serde_name(&r.confidence),
+            r.score,
+            serde_name(&r.action),
+            r.occurrences.len(),
+            escape(&original),
+            n = i + 1,
+            class = css_class(&r.category),
+        );
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; margin-bottom: 2em; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; vertical-align: top; }
+.summary th { background: #f4f4f4; }
+.records tr:target { outline: 2px solid #333; }
+pre.text { white-space: pre-wrap; border: 1px solid #ccc; padding: 1em; line-height: 1.6; }
+.ph { padding: 0 0.2em; border-radius: 3px; color: #111; text-decoration: none; }
+";
+
+/// Span in the anonymized text and record index of every replacement,
+/// in text order.
+///
+/// Occurrences are recorded as spans of the original text; each one
+/// shifts the following text by the difference between its replacement
+/// and the original length. Kept values are replaced by themselves.
+fn locate(report: &AuditReport, anonymized_text: &str) -> Vec<(usize, usize, usize)> {
+    let mut occurrences: Vec<(usize, usize, usize)> = report.replacements.iter()
+        .enumerate()
+        .flat_map(|(index, r)| r.occurrences.iter().map(move |s| (s.start, s.end, index)))
+        .collect();
+    occurrences.sort();
+
+    let mut located = Vec::with_capacity(occurrences.len());
+    let mut shift: isize = 0;
+    let mut free_from = 0;
+    for (start, end, index) in occurrences {
+        let record: &ReplacementRecord = &report.replacements[index];
+        let output_start = start as isize + shift;
+        // Kept values are not in the report, only their length
+        let placeholder = record.placeholder.as_deref().filter(|_| record.action != Action::Keep);
+        let replaced_len = placeholder.map_or(end - start, str::len);
+        shift += replaced_len as isize - (end - start) as isize;
+
+        let Ok(output_start) = usize::try_from(output_start) else {
+            continue;
+        };
+        let output_end = output_start + replaced_len;
+        let found = anonymized_text.get(output_start..output_end)
+            .is_some_and(|replaced| placeholder.is_none_or(|p| p == replaced));
+        if output_start >= free_from && found {
+            located.push((output_start, output_end, index));
+            free_from = output_end;
+        }
+    }
+    located
+}
+
+fn categories(report: &AuditReport) -> Vec<Category> {
+    let mut categories: Vec<Category> = report.replacements.iter().map(|r| r.category.clone()).collect();
+    categories.sort_by_key(Category::placeholder_name);
+    categories.dedup();
+    categories
+}
+
+/// Stable hue for a category, so its color is the same in every report
+fn hue(category: &Category) -> u32 {
+    category.placeholder_name().bytes().fold(0u32, |h, b| h.wrapping_mul(31).wrapping_add(b as u32)) % 360
+}
+
+fn css_class(category: &Category) -> String {
+    category.placeholder_name().chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect()
+}
+
+/// Name of a unit variant as written in reports and configuration
+fn serde_name<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// First characters that make spreadsheets evaluate a cell (OWASP CSV
+/// injection)
+const FORMULA_PREFIXES: [char; 6] = ['=', '+', '-', '@', '\t', '\r'];
+
+/// Append a CSV row, quoting fields that contain separators, quotes or
+/// line breaks. Fields a spreadsheet would evaluate as a formula (`+34…`,
+/// `=…`, or behind a leading tab or carriage return) are prefixed with `'`.
+fn push_row(csv: &mut String, fields: &[String]) {
+    let row: Vec<String> = fields.iter()
+        .map(|f| {
+            let f = if f.starts_with(FORMULA_PREFIXES) { format!("'{}", f) } else { f.clone() };
+            if f.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", f.replace('"', "\"\""))
+            } else {
+                f
+            }
+        })
+        .collect();
+    csv.push_str(&row.join(","));
+    csv.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_report;
+
+    const REPORT: &str = r#"{
+        "version": "0.2.0", "timestamp": "2024-12-27T10:30:00Z", "input_hash": "a", "config_hash": "b",
+        "disclosure": "full",
+        "statistics": {"total_matches": 3, "matches_by_category": {"EMAIL": 2, "PHONE": 1}, "conflicts_resolved": 0,
+            "suppressed_by_allowlist": 0, "discarded_by_context": 0, "below_threshold": 0, "processing_time_ms": 1,
+            "timings": {"detection_us": 610, "policy_us": 3},
+            "detectors": {"email": {"candidates": 2, "accepted": 2, "duration_us": 120}}},
+        "replacements": [
+            {"placeholder": "[EMAIL_001]", "category": "EMAIL", "detector_id": "email", "confidence": "pattern_only",
+             "score": 0.9, "occurrences": [{"start": 5, "end": 18}, {"start": 36, "end": 49}],
+             "original_value": "=cmd@evil.com", "action": "redact"},
+            {"category": "PHONE", "detector_id": "phone", "confidence": "pattern_only", "score": 0.8,
+             "occurrences": [{"start": 23, "end": 32}], "action": "keep"}
+        ],
+        "conflicts": []
+    }"#;
+
+    #[test]
+    fn formula_prefixes_are_neutralized() {
+        let mut csv = String::new();
+        let fields: Vec<String> = ["=1+1", "+34 600", "-2", "@SUM(A1)", "\tx", "\r=1", "plain", "a,b"]
+            .iter().map(|f| f.to_string()).collect();
+        push_row(&mut csv, &fields);
+        assert_eq!(csv, "'=1+1,'+34 600,'-2,'@SUM(A1),'\tx,\"'\r=1\",plain,\"a,b\"\n");
+    }
+
+    #[test]
+    fn records_csv_has_one_row_per_record() {
+        let csv = records_csv(&read_report(REPORT).unwrap());
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("[EMAIL_001],EMAIL,email,pattern_only,0.9,redact,,2,5-18;36-49,'=cmd@evil.com,"));
+        assert!(lines[2].starts_with(",PHONE,phone,pattern_only,0.8,keep,,1,23-32,,"));
+    }
+
+    #[test]
+    fn archived_reports_are_upgraded_for_export() {
+        let legacy = serde_json::json!({
+            "version": "0.1.0", "timestamp": "2024-12-27T10:30:00Z", "input_hash": "a", "config_hash": "b",
+            "statistics": {"total_matches": 1, "matches_by_category": {"NationalId": 1}, "conflicts_resolved": 0, "processing_time_ms": 8},
+            "replacements": [{"placeholder": "[NATIONAL_ID_001]", "category": "NationalId", "detector_id": "spanish_id",
+                "confidence": "PatternOnly", "original_span": {"start": 4, "end": 13}, "original_value": "12345678Z"}]
+        });
+        let report = crate::read_report_value(legacy).unwrap();
+        assert_eq!(report.replacements[0].category, Category::NationalId);
+        assert!(records_csv(&report).contains("[NATIONAL_ID_001],NATIONAL_ID,spanish_id,pattern_only,"));
+        assert!(records_csv(&report).contains(",redact,,1,4-13,12345678Z,"));
+    }
+
+    #[test]
+    fn statistics_csv_matches_the_report() {
+        let csv = statistics_csv(&read_report(REPORT).unwrap());
+        for row in [
+            "disclosure,full",
+            "matches_by_category.EMAIL,2",
+            "timings.detection_us,610",
+            "timings.policy_us,3",
+            "timings.replacement_us,0",
+            "detectors.email.candidates,2",
+            "detectors.email.accepted,2",
+            "detectors.email.duration_us,120",
+        ] {
+            assert!(csv.lines().any(|line| line == row), "{} missing", row);
+        }
+    }
+
+    #[test]
+    fn html_highlights_placeholders_and_kept_values() {
+        let text = "Mail [EMAIL_001] tel 612345678 or [EMAIL_001].";
+        let html = report_html(&read_report(REPORT).unwrap(), text);
+        assert_eq!(html.matches("href=\"#record-1\"").count(), 2);
+        assert!(html.contains("href=\"#record-2\" title=\"PHONE\">612345678</a>"));
+    }
+}
@@ -0,0 +1,297 @@
+// src/ledger.rs
+
+use serde::{Serialize, Deserialize};
+use sha2::{Sha256, Digest};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use chrono::{DateTime, Utc};
+use crate::{AnonymizeError, Result};
+use crate::audit_report::AuditReport;
+use crate::config::LedgerConfig;
+use crate::signing::canonical_json;
+
+/// `previous_hash` of the first entry
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Record of one anonymization run.
+///
+/// Entries hold digests and counts, never values: the ledger can be kept
+/// as a processing record long after the documents are gone.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LedgerEntry {
+    /// Position in the ledger, from 1
+    pub sequence: u64,
+    pub timestamp: DateTime<Utc>,
+    pub input_hash: String,
+    pub config_hash: String,
+    /// SHA-256 of the anonymized text
+    pub output_hash: Option<String>,
+    /// SHA-256 of the canonical audit report
+    pub report_hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    pub total_matches: usize,
+    pub matches_by_category: BTreeMap<String, usize>,
+    /// `entry_hash` of the preceding entry
+    pub previous_hash: String,
+    /// SHA-256 of the canonical entry without this field
+    pub entry_hash: String,
+}
+
+impl LedgerEntry {
+    fn compute_hash(&self) -> String {
+        let mut unhashed = serde_json::to_value(self).expect("BUG: ledger entries serialize to JSON");
+        if let Some(object) = unhashed.as_object_mut() {
+            object.remove("entry_hash");
+        }
+        format!("{:x}", Sha256::digest(canonical_json(&unhashed)))
+    }
+}
+
+/// Result of checking a ledger's hash chain
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ChainStatus {
+    /// Every entry is unaltered and links to its predecessor
+    Intact { entries: usize },
+    /// The first line (from 1) found altered, removed or out of place
+    Broken { line: usize, reason: String },
+}
+
+/// Append-only, hash-chained JSON-lines file recording every run.
+///
+/// Each line is a `LedgerEntry` whose `entry_hash` covers its content and
+/// the previous entry's hash, so editing, deleting or reordering past
+/// entries breaks the chain from that point on. The ledger also remembers
+/// the last entry it holds, so removing the file or entries from its end
+/// is detected while it is open. Appends from several threads are
+/// serialized; the file must not be written by several processes at once.
+///
+/// All methods do blocking file I/O.
+pub struct Ledger {
+    path: PathBuf,
+    /// Sequence and hash of the last entry
+    tail: Mutex<(u64, String)>,
+}
+
+impl Ledger {
+    /// Open a ledger, creating the file on the first append
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut tail = (0, GENESIS_HASH.to_string());
+        if path.exists() {
+            if let Some(last) = read_entries(&path)?.pop() {
+                tail = (last.sequence, last.entry_hash);
+            }
+        }
+        Ok(Self {
+            path,
+            tail: Mutex::new(tail),
+        })
+    }
+
+    /// Open the ledger described by `[ledger]`, or `None` when no path is set
+    pub fn from_config(config: &LedgerConfig) -> Result<Option<Self>> {
+        config.path.as_ref().map(Self::open).transpose()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Record a run and return its entry
+    pub fn append(&self, report: &AuditReport) -> Result<LedgerEntry> {
+        let mut tail = self.tail.lock().expect("BUG: ledger lock poisoned");
+
+        let mut entry = LedgerEntry {
+            sequence: tail.0 + 1,
+            timestamp: report.timestamp,
+            input_hash: report.input_hash.clone(),
+            config_hash: report.config_hash.clone(),
+            output_hash: report.output_hash.as_ref().map(|h| h.value.clone()),
+            report_hash: format!("{:x}", Sha256::digest(canonical_json(report))),
+            session_id: report.session_id.clone(),
+            total_matches: report.statistics.total_matches,
+            matches_by_category: report.statistics.matches_by_category.clone(),
+            previous_hash: tail.1.clone(),
+            entry_hash: String::new(),
+        };
+        entry.entry_hash = entry.compute_hash();
+
+        let mut line = serde_json::to_string(&entry).expect("BUG: ledger entries serialize to JSON");
+        line.push('\n');
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        let length = file.metadata()?.len();
+        if let Err(e) = file.write_all(line.as_bytes()).and_then(|_| file.sync_data()) {
+            // A partial line would make every later entry unreadable
+            let _ = file.set_len(length);
+            return Err(e.into());
+        }
+
+        *tail = (entry.sequence, entry.entry_hash.clone());
+        Ok(entry)
+    }
+
+    /// Every run recorded, oldest first
+    pub fn entries(&self) -> Result<Vec<LedgerEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        read_entries(&self.path)
+    }
+
+    /// Runs whose input had this SHA-256
+    pub fn find_by_input_hash(&self, input_hash: &str) -> Result<Vec<LedgerEntry>> {
+        let mut entries = self.entries()?;
+        entries.retain(|e| e.input_hash == input_hash);
+        Ok(entries)
+    }
+
+    /// Check every entry's hash and its link to the previous one, and that
+    /// the file still ends with the last entry known to this ledger
+    pub fn verify(&self) -> Result<ChainStatus> {
+        // Appends wait for the check, so the tail stays the file's last entry
+        let tail = self.tail.lock().expect("BUG: ledger lock poisoned");
+        if !self.path.exists() {
+            return Ok(match tail.0 {
+                0 => ChainStatus::Intact { entries: 0 },
+                _ => ChainStatus::Broken { line: 1, reason: "ledger file is missing".to_string() },
+            });
+        }
+
+        let mut previous_hash = GENESIS_HASH.to_string();
+        let mut entries = 0;
+        for (index, line) in BufReader::new(File::open(&self.path)?).lines().enumerate() {
+            let line = line?;
+            let broken = |reason: &str| Ok(ChainStatus::Broken { line: index + 1, reason: reason.to_string() });
+
+            let Ok(entry) = serde_json::from_str::<LedgerEntry>(&line) else {
+                return broken("unreadable entry");
+            };
+            if entry.sequence != entries as u64 + 1 {
+                return broken("sequence gap");
+            }
+            if entry.previous_hash != previous_hash {
+                return broken("previous_hash does not match the preceding entry");
+            }
+            if entry.entry_hash != entry.compute_hash() {
+                return broken("entry_hash does not match the entry");
+            }
+            if entry.sequence > tail.0 {
+                return broken("entry not appended by this ledger");
+            }
+            previous_hash = entry.entry_hash;
+            entries += 1;
+        }
+
+        if entries < tail.0 as usize {
+            return Ok(ChainStatus::Broken {
+                line: entries + 1,
+                reason: format!("entries {} to {} are missing", entries + 1, tail.0),
+            });
+        }
+        if previous_hash != tail.1 {
+            return Ok(ChainStatus::Broken {
+                line: entries,
+                reason: "last entry does not match the one appended".to_string(),
+            });
+        }
+        Ok(ChainStatus::Intact { entries })
+    }
+}
+
+fn read_entries(path: &Path) -> Result<Vec<LedgerEntry>> {
+    BufReader::new(File::open(path)?)
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            serde_json::from_str(&line?).map_err(|e| AnonymizeError::LedgerError {
+                message: format!("{} line {}: {}", path.display(), index + 1, e),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Anonymizer, AnonymizeConfig};
+
+    /// Ledger in a fresh temporary file
+    fn ledger(name: &str) -> Ledger {
+        let path = std::env::temp_dir().join(format!("anonymize-ledger-{}-{}.jsonl", std::process::id(), name));
+        let _ = std::fs::remove_file(&path);
+        Ledger::open(path).unwrap()
+    }
+
+    fn record(ledger: &Ledger, texts: &[&str]) {
+        let anonymizer = Anonymizer::from_config(&AnonymizeConfig::default()).unwrap();
+        for text in texts {
+            ledger.append(&anonymizer.anonymize(text).unwrap().report).unwrap();
+        }
+    }
+
+    fn lines(ledger: &Ledger) -> Vec<String> {
+        std::fs::read_to_string(ledger.path()).unwrap().lines().map(str::to_string).collect()
+    }
+
+    fn rewrite(ledger: &Ledger, lines: &[String]) {
+        let content: String = lines.iter().map(|l| format!("{}\n", l)).collect();
+        std::fs::write(ledger.path(), content).unwrap();
+    }
+
+    #[test]
+    fn intact_chain_verifies_and_reopens() {
+        let ledger = ledger("intact");
+        assert_eq!(ledger.verify().unwrap(), ChainStatus::Intact { entries: 0 });
+        record(&ledger, &["a@example.com", "no data", "b@example.com"]);
+        assert_eq!(ledger.verify().unwrap(), ChainStatus::Intact { entries: 3 });
+
+        let entries = ledger.entries().unwrap();
+        assert_eq!(entries[0].previous_hash, GENESIS_HASH);
+        assert_eq!(entries[2].previous_hash, entries[1].entry_hash);
+        assert_eq!(ledger.find_by_input_hash(&entries[1].input_hash).unwrap().len(), 1);
+
+        let reopened = Ledger::open(ledger.path()).unwrap();
+        record(&reopened, &["c@example.com"]);
+        assert_eq!(reopened.verify().unwrap(), ChainStatus::Intact { entries: 4 });
+        std::fs::remove_file(ledger.path()).unwrap();
+    }
+
+    #[test]
+    fn edited_and_reordered_entries_break_the_chain() {
+        let ledger = ledger("edited");
+        record(&ledger, &["a@example.com", "b@example.com", "c@example.com"]);
+        let original = lines(&ledger);
+
+        let mut edited = original.clone();
+        edited[1] = edited[1].replace("\"total_matches\":1", "\"total_matches\":0");
+        rewrite(&ledger, &edited);
+        assert!(matches!(ledger.verify().unwrap(), ChainStatus::Broken { line: 2, .. }));
+
+        let mut reordered = original.clone();
+        reordered.swap(0, 1);
+        rewrite(&ledger, &reordered);
+        assert!(matches!(ledger.verify().unwrap(), ChainStatus::Broken { line: 1, .. }));
+
+        rewrite(&ledger, &[original[0].clone(), original[2].clone()]);
+        assert!(matches!(ledger.verify().unwrap(), ChainStatus::Broken { line: 2, .. }));
+        std::fs::remove_file(ledger.path()).unwrap();
+    }
+
+    #[test]
+    fn truncation_and_deletion_are_detected() {
+        let ledger = ledger("truncated");
+        record(&ledger, &["a@example.com", "b@example.com", "c@example.com"]);
+        let original = lines(&ledger);
+
+        rewrite(&ledger, &original[..2]);
+        assert!(matches!(ledger.verify().unwrap(), ChainStatus::Broken { line: 3, .. }));
+
+        std::fs::remove_file(ledger.path()).unwrap();
+        assert!(matches!(ledger.verify().unwrap(), ChainStatus::Broken { line: 1, .. }));
+    }
+}
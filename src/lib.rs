@@ -5,25 +5,64 @@
 //! Deterministic text anonymization engine.
 
 mod error;
+mod config;
 mod normalizer;
 mod detector;
 mod conflict_resolver;
+mod policy;
 mod replacement_engine;
 mod audit_report;
 mod engine;
+mod vault;
+mod session;
+mod scan;
+mod context;
+mod value_lists;
+mod signing;
+mod ledger;
+mod export;
+mod report_format;
 pub mod utils;
 pub mod web;
 pub mod document_processor; // New: document processing
 
 pub use error::AnonymizeError;
-pub use normalizer::{normalize, NormalizedText};
+pub use config::{
+    AnonymizeConfig, LimitsConfig, DetectorConfig, CustomDetectorConfig, GazetteerConfig,
+    LabelsConfig, DocumentTemplateConfig, ContextConfig, ContextKeywords, ValueListsConfig,
+    ValueListEntries, ConflictsConfig, PolicyConfig, ReplacementConfig, HmacConfig, SyntheticConfig, MaskConfig,
+    AuditConfig, SigningConfig, LedgerConfig,
+    MaskRule, SecretSource, DEFAULT_CONFIG_FILE, CONFIG_PATH_ENV,
+};
+pub use normalizer::{normalize, normalize_with_limit, NormalizedText, OffsetMap, DEFAULT_MAX_INPUT_SIZE};
 pub use detector::{
     Detector, CandidateMatch, Category, Span, DetectorId, Confidence, ValidationResult,
+    PriorityOverride, BUILTIN_DETECTORS, builtin_detector,
     EmailDetector, PhoneDetector, SpanishIdDetector, IbanDetector, CreditCardDetector,
     SsnDetector, ProjectCodeDetector, ContractNumberDetector, WorkOrderDetector,
-    PurchaseOrderDetector, SerialNumberDetector, CostCenterDetector,
+    PurchaseOrderDetector, SerialNumberDetector, CostCenterDetector, RegexDetector,
+    ChecksumValidator, GazetteerDetector, LabelDetector, DocumentNumberDetector,
+    DEFAULT_DOCUMENT_TEMPLATE,
 };
 pub use engine::{Anonymizer, AnonymizationOutput};
-pub use audit_report::AuditReport;
+pub use policy::{Action, Policy, PlannedReplacement};
+pub use replacement_engine::{ReplacementEngine, KeyedTokenizer, SyntheticGenerator, Masker};
+pub use conflict_resolver::{
+    ConflictResolver, ConflictStrategy, ResolutionStrategy, LeftmostLongest, PriorityFirst,
+    ConfidenceFirst, MergeAdjacent, Resolution, Conflict, ConflictRule,
+};
+pub use audit_report::{
+    AuditReport, Statistics, StageTimings, DetectorStatistics, ReplacementRecord, ConflictRecord,
+    ContentHash, Disclosure, AuditDisclosure,
+};
+pub use signing::{ReportSigner, ReportSignature, SignatureCheck, Verification, SIGNATURE_ALGORITHM};
+pub use session::AnonymizationSession;
+pub use scan::{ScanReport, Finding};
+pub use context::ContextAnalyzer;
+pub use value_lists::{ValueLists, ListVerdict};
+pub use ledger::{Ledger, LedgerEntry, ChainStatus, GENESIS_HASH};
+pub use report_format::{read_report, read_report_value, upgrade_report, REPORT_VERSION, REPORT_SCHEMA};
+pub use export::{ExportFormat, records_csv, statistics_csv, report_html};
+pub use vault::{MappingVault, SealedVault, deanonymize};
 
 pub type Result<T> = std::result::Result<T, AnonymizeError>;
@@ -1,9 +1,23 @@
 // src/main.rs
 
+use anonymize::{AnonymizeConfig, ReportSigner, SignatureCheck};
 use anonymize::web::start_server;
+use std::path::PathBuf;
+
+const VERIFY_USAGE: &str =
+    "usage: anonymize verify --report <report.json> --original <input.txt> --anonymized <output.txt>";
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Engine configuration ($ANONYMIZE_CONFIG, ./anonymize.toml or defaults)
+    let config = AnonymizeConfig::load()?;
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("verify") {
+        let valid = verify(&config, &args[1..])?;
+        std::process::exit(if valid { 0 } else { 1 });
+    }
+
     // Port configurable via environment variable
     let port: u16 = std::env::var("PORT")
         .unwrap_or_else(|_| "3000".to_string())
@@ -11,7 +25,55 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .expect("PORT must be a valid number");
 
     // Start web server
-    start_server(port).await?;
+    start_server(port, config).await?;
 
     Ok(())
 }
+
+/// Check a signed audit report against the original and anonymized texts,
+/// printing each check; returns whether all of them pass
+fn verify(config: &AnonymizeConfig, args: &[String]) -> Result<bool, Box<dyn std::error::Error>> {
+    let mut report = None;
+    let mut original = None;
+    let mut anonymized = None;
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let slot = match flag.as_str() {
+            "--report" => &mut report,
+            "--original" => &mut original,
+            "--anonymized" => &mut anonymized,
+            _ => return Err(format!("unknown argument '{}'\n{}", flag, VERIFY_USAGE).into()),
+        };
+        let value = args.next().ok_or_else(|| format!("{} needs a value\n{}", flag, VERIFY_USAGE))?;
+        *slot = Some(PathBuf::from(value));
+    }
+    let (Some(report), Some(original), Some(anonymized)) = (report, original, anonymized) else {
+        return Err(VERIFY_USAGE.into());
+    };
+
+    let signer = ReportSigner::from_config(&config.audit.signing)?
+        .ok_or("no signing keys configured in [audit.signing]")?;
+
+    // Accept a bare report or a whole /api/anonymize response. The
+    // signature covers the report as stored, in whatever format version.
+    let mut json: serde_json::Value = serde_json::from_slice(&std::fs::read(&report)?)?;
+    if let Some(inner) = json.get_mut("audit_report") {
+        json = inner.take();
+    }
+    let verification = signer.verify_json(&json, &std::fs::read(&original)?, &std::fs::read(&anonymized)?);
+    let config_hash = json.get("config_hash").and_then(serde_json::Value::as_str).unwrap_or_default();
+
+    let signature = match &verification.signature {
+        SignatureCheck::Valid => "valid".to_string(),
+        SignatureCheck::Invalid => "INVALID".to_string(),
+        SignatureCheck::Missing => "MISSING".to_string(),
+        SignatureCheck::UnknownKey(id) => format!("UNKNOWN KEY '{}'", id),
+    };
+    let check = |ok: bool| if ok { "matches" } else { "DOES NOT MATCH" };
+    println!("signature:  {}", signature);
+    println!("original:   {}", check(verification.input_matches));
+    println!("anonymized: {}", check(verification.output_matches));
+    println!("config:     {}", config_hash);
+
+    Ok(verification.is_valid())
+}
@@ -1,13 +1,95 @@
 // src/normalizer.rs
 
 use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::canonical_combining_class;
 use crate::{AnonymizeError, Result};
+use crate::detector::Span;
 
 /// Text after normalization with metadata
 pub struct NormalizedText {
     pub content: String,
     pub original_len: usize,
     pub transformations_applied: Vec<NormalizationType>,
+    /// Maps byte offsets of `content` back to the input text
+    pub offsets: OffsetMap,
+}
+
+/// Correspondence between normalized and original byte offsets.
+///
+/// The normalized text is a sequence of segments, each produced by one
+/// piece of the input: a run of characters left unchanged, a character
+/// cluster recomposed by NFC, or a whitespace run collapsed to one space.
+#[derive(Debug, Clone, Default)]
+pub struct OffsetMap {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    normalized: usize,
+    normalized_len: usize,
+    original: usize,
+    original_len: usize,
+    /// Offsets inside the segment map one to one (unchanged text, or
+    /// characters replaced by others of the same width)
+    identity: bool,
+}
+
+impl OffsetMap {
+    pub(crate) fn push(&mut self, normalized: usize, normalized_len: usize, original: usize, original_len: usize, identity: bool) {
+        let segment = Segment { normalized, normalized_len, original, original_len, identity };
+
+        // Merge runs of unchanged text to keep the map small
+        if let Some(last) = self.segments.last_mut() {
+            if last.identity
+                && identity
+                && last.normalized + last.normalized_len == normalized
+                && last.original + last.original_len == original
+            {
+                last.normalized_len += normalized_len;
+                last.original_len += original_len;
+                return;
+            }
+        }
+        self.segments.push(segment);
+    }
+
+    /// Span of the input covering a span of the normalized text.
+    ///
+    /// Boundaries falling inside a recomposed cluster or a collapsed
+    /// whitespace run are widened to include the whole cluster or run.
+    pub fn to_original(&self, span: Span) -> Span {
+        if self.segments.is_empty() {
+            return span;
+        }
+
+        let start_segment = self.segment_at(span.start);
+        let start = if start_segment.identity {
+            start_segment.original + (span.start - start_segment.normalized)
+        } else {
+            start_segment.original
+        };
+
+        let end = if span.end <= span.start {
+            start
+        } else {
+            let end_segment = self.segment_at(span.end - 1);
+            if end_segment.identity {
+                end_segment.original + (span.end - end_segment.normalized)
+            } else {
+                end_segment.original + end_segment.original_len
+            }
+        };
+
+        Span { start, end }
+    }
+
+    fn segment_at(&self, normalized: usize) -> Segment {
+        let index = self.segments
+            .partition_point(|s| s.normalized <= normalized)
+            .saturating_sub(1);
+        self.segments[index]
+    }
 }
 
 /// Types of normalization transformations
@@ -18,39 +100,136 @@ pub enum NormalizationType {
     Trim,
 }
 
+/// Default maximum input size accepted by `normalize`
+pub const DEFAULT_MAX_INPUT_SIZE: usize = 100_000_000; // 100 MB
+
 /// Normalize text according to specified rules
 pub fn normalize(text: &str) -> Result<NormalizedText> {
-    const MAX_INPUT_SIZE: usize = 100_000_000; // 100 MB
-    
+    normalize_with_limit(text, DEFAULT_MAX_INPUT_SIZE)
+}
+
+/// Normalize text, rejecting inputs larger than `max_input_size` bytes
+pub fn normalize_with_limit(text: &str, max_input_size: usize) -> Result<NormalizedText> {
     // Validate input size
-    if text.len() > MAX_INPUT_SIZE {
+    if text.len() > max_input_size {
         return Err(AnonymizeError::InputTooLarge {
             size: text.len(),
-            max: MAX_INPUT_SIZE,
+            max: max_input_size,
         });
     }
     
     let original_len = text.len();
-    let mut transformations = Vec::new();
-    
-    // Apply Unicode NFC normalization
-    let mut normalized: String = text.nfc().collect();
-    transformations.push(NormalizationType::UnicodeNfc);
+    let mut content = String::with_capacity(text.len());
+    let mut offsets = OffsetMap::default();
     
-    // Collapse whitespace sequences to single spaces
-    normalized = normalized
-        .split_whitespace()
-        .collect::<Vec<_>>()
-        .join(" ");
-    transformations.push(NormalizationType::WhitespaceCollapse);
-    
-    // Trim leading/trailing whitespace
-    normalized = normalized.trim().to_string();
-    transformations.push(NormalizationType::Trim);
+    // Walk the non-whitespace tokens of the input. Whitespace between two
+    // tokens collapses to a single space; leading and trailing whitespace
+    // is trimmed. Each token is NFC-normalized one character cluster
+    // (starter plus combining marks) at a time so every normalized byte
+    // can be traced back to its source.
+    let mut previous_end: Option<usize> = None;
+    for (start, token) in tokens(text) {
+        if let Some(previous_end) = previous_end {
+            let gap = &text[previous_end..start];
+            offsets.push(content.len(), 1, previous_end, gap.len(), gap == " ");
+            content.push(' ');
+        }
+        
+        if token.is_ascii() {
+            offsets.push(content.len(), token.len(), start, token.len(), true);
+            content.push_str(token);
+        } else {
+            for (cluster_start, cluster) in clusters(token) {
+                let composed: String = cluster.nfc().collect();
+                let unchanged = composed == cluster;
+                offsets.push(content.len(), composed.len(), start + cluster_start, cluster.len(), unchanged);
+                content.push_str(&composed);
+            }
+        }
+        previous_end = Some(start + token.len());
+    }
     
     Ok(NormalizedText {
-        content: normalized,
+        content,
         original_len,
-        transformations_applied: transformations,
+        transformations_applied: vec![
+            NormalizationType::UnicodeNfc,
+            NormalizationType::WhitespaceCollapse,
+            NormalizationType::Trim,
+        ],
+        offsets,
     })
 }
+
+/// Non-whitespace runs of `text`, with their byte offsets
+fn tokens(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split(char::is_whitespace)
+        .filter(|token| !token.is_empty())
+        .map(move |token| (token.as_ptr() as usize - text.as_ptr() as usize, token))
+}
+
+/// Character clusters of a token: a starter followed by its combining marks
+fn clusters(token: &str) -> Vec<(usize, &str)> {
+    let mut clusters = Vec::new();
+    let mut cluster_start = 0;
+    for (i, c) in token.char_indices().skip(1) {
+        if canonical_combining_class(c) == 0 {
+            clusters.push((cluster_start, &token[cluster_start..i]));
+            cluster_start = i;
+        }
+    }
+    clusters.push((cluster_start, &token[cluster_start..]));
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn original<'a>(text: &'a str, normalized: &NormalizedText, needle: &str) -> &'a str {
+        let start = normalized.content.find(needle).unwrap();
+        let span = normalized.offsets.to_original(Span { start, end: start + needle.len() });
+        &text[span.start..span.end]
+    }
+
+    #[test]
+    fn collapsed_whitespace_maps_back_to_the_input() {
+        let text = "  Call\t\t612  345\n678 today ";
+        let normalized = normalize(text).unwrap();
+        assert_eq!(normalized.content, "Call 612 345 678 today");
+        assert_eq!(original(text, &normalized, "612 345 678"), "612  345\n678");
+        assert_eq!(original(text, &normalized, "today"), "today");
+        assert_eq!(original(text, &normalized, "Call"), "Call");
+    }
+
+    #[test]
+    fn decomposed_characters_map_to_whole_clusters() {
+        // "José" with a combining acute accent, recomposed to one character
+        let text = "Sr. Jose\u{301} Martínez";
+        let normalized = normalize(text).unwrap();
+        assert_eq!(normalized.content, "Sr. José Martínez");
+        assert_eq!(original(text, &normalized, "José"), "Jose\u{301}");
+        assert_eq!(original(text, &normalized, "Martínez"), "Martínez");
+        // A boundary inside the recomposed character widens to the cluster
+        let start = normalized.content.find('é').unwrap();
+        let span = normalized.offsets.to_original(Span { start, end: start + 1 });
+        assert_eq!(&text[span.start..span.end], "e\u{301}");
+    }
+
+    #[test]
+    fn boundaries_inside_a_collapsed_run_take_the_whole_run() {
+        let text = "a \n\n b";
+        let normalized = normalize(text).unwrap();
+        assert_eq!(normalized.offsets.to_original(Span { start: 1, end: 2 }), Span { start: 1, end: 5 });
+        assert_eq!(normalized.offsets.to_original(Span { start: 0, end: 3 }), Span { start: 0, end: 6 });
+        assert_eq!(normalized.offsets.to_original(Span { start: 2, end: 2 }), Span { start: 5, end: 5 });
+    }
+
+    #[test]
+    fn empty_and_oversized_inputs() {
+        let normalized = normalize(" \n ").unwrap();
+        assert_eq!(normalized.content, "");
+        assert_eq!(normalized.offsets.to_original(Span { start: 0, end: 0 }), Span { start: 0, end: 0 });
+        assert!(matches!(normalize_with_limit("abcdef", 5), Err(AnonymizeError::InputTooLarge { size: 6, max: 5 })));
+    }
+}
@@ -0,0 +1,157 @@
+// src/policy.rs
+
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use crate::config::PolicyConfig;
+use crate::detector::{CandidateMatch, Category};
+
+/// Treatment applied to a detected value
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Sequential placeholder, e.g. `[EMAIL_001]`
+    #[default]
+    Redact,
+    /// Partially masked value, e.g. `****@acme.com`
+    Mask,
+    /// Keyed HMAC token, e.g. `[IBAN_7f3a9c21]`
+    Hash,
+    /// Realistic fake value of the same format
+    Synthesize,
+    /// Left unchanged, but still recorded in the audit report
+    Keep,
+}
+
+impl Action {
+    /// Whether the replacement identifies a single original value, so it
+    /// can be recorded in a `MappingVault`. Masked values do not (two
+    /// e-mails of the same domain may mask identically) and kept values
+    /// need no mapping.
+    pub fn is_reversible(&self) -> bool {
+        matches!(self, Action::Redact | Action::Hash | Action::Synthesize)
+    }
+}
+
+/// A resolved match together with the action the policy assigned to it
+#[derive(Debug, Clone)]
+pub struct PlannedReplacement {
+    pub candidate: CandidateMatch,
+    pub action: Action,
+}
+
+/// Maps each category to an action.
+///
+/// Categories are named by their placeholder name (`IBAN`, `WORK_ORDER`,
+/// or the upper-cased name of a `Category::Custom`); unlisted categories
+/// get the default action.
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    default_action: Action,
+    categories: BTreeMap<String, Action>,
+}
+
+impl Policy {
+    /// A policy applying the same action to every category
+    pub fn uniform(action: Action) -> Self {
+        Self {
+            default_action: action,
+            categories: BTreeMap::new(),
+        }
+    }
+
+    /// Build the policy described by `[policy]`
+    pub fn from_config(config: &PolicyConfig) -> Self {
+        let mut policy = Self::uniform(config.default);
+        for (category, action) in &config.categories {
+            policy.set_action(category, *action);
+        }
+        policy
+    }
+
+    /// Override the action of one category (by placeholder name)
+    pub fn set_action(&mut self, category: &str, action: Action) {
+        self.categories.insert(category.to_uppercase(), action);
+    }
+
+    pub fn action_for(&self, category: &Category) -> Action {
+        self.categories
+            .get(&category.placeholder_name())
+            .copied()
+            .unwrap_or(self.default_action)
+    }
+
+    /// Every action this policy can produce
+    pub fn actions(&self) -> impl Iterator<Item = Action> + '_ {
+        std::iter::once(self.default_action).chain(self.categories.values().copied())
+    }
+
+    /// Assign an action to each resolved match
+    pub fn plan(&self, matches: Vec<CandidateMatch>) -> Vec<PlannedReplacement> {
+        matches
+            .into_iter()
+            .map(|candidate| PlannedReplacement {
+                action: self.action_for(&candidate.category),
+                candidate,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detector::{Confidence, Span};
+
+    fn policy(toml: &str) -> Policy {
+        Policy::from_config(&toml::from_str(toml).unwrap())
+    }
+
+    fn candidate(category: Category) -> CandidateMatch {
+        CandidateMatch {
+            span: Span { start: 0, end: 1 },
+            detector_id: "test".to_string(),
+            category,
+            priority: 50,
+            confidence: Confidence::PatternOnly,
+            score: 0.8,
+            raw_value: "x".to_string(),
+            normalized_value: None,
+        }
+    }
+
+    #[test]
+    fn listed_categories_get_their_own_action() {
+        let policy = policy("[categories]\nIBAN = \"mask\"\nemail = \"hash\"\nLOT_CODE = \"keep\"\n");
+        assert_eq!(policy.action_for(&Category::Iban), Action::Mask);
+        assert_eq!(policy.action_for(&Category::Email), Action::Hash);
+        assert_eq!(policy.action_for(&Category::Custom("lot_code".to_string())), Action::Keep);
+    }
+
+    #[test]
+    fn unlisted_categories_fall_back_to_the_default() {
+        assert_eq!(Policy::default().action_for(&Category::Phone), Action::Redact);
+
+        let policy = policy("default = \"synthesize\"\n[categories]\nPHONE = \"redact\"\n");
+        assert_eq!(policy.action_for(&Category::Phone), Action::Redact);
+        assert_eq!(policy.action_for(&Category::NationalId), Action::Synthesize);
+        assert_eq!(policy.actions().collect::<Vec<_>>(), [Action::Synthesize, Action::Redact]);
+    }
+
+    #[test]
+    fn plans_follow_the_policy() {
+        let mut policy = Policy::uniform(Action::Hash);
+        policy.set_action("credit_card", Action::Mask);
+        let planned = policy.plan(vec![candidate(Category::CreditCard), candidate(Category::Iban)]);
+        let actions: Vec<(Category, Action)> = planned.into_iter().map(|p| (p.candidate.category, p.action)).collect();
+        assert_eq!(actions, [(Category::CreditCard, Action::Mask), (Category::Iban, Action::Hash)]);
+    }
+
+    #[test]
+    fn only_mask_and_keep_are_irreversible() {
+        let reversible: Vec<Action> = [Action::Redact, Action::Mask, Action::Hash, Action::Synthesize, Action::Keep]
+            .into_iter()
+            .filter(Action::is_reversible)
+            .collect();
+        assert_eq!(reversible, [Action::Redact, Action::Hash, Action::Synthesize]);
+    }
+}
@@ -0,0 +1,202 @@
+// src/replacement_engine/keyed.rs
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use zeroize::Zeroizing;
+use crate::{AnonymizeError, Result};
+use crate::config::HmacConfig;
+use crate::detector::{CandidateMatch, Category};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Hex characters kept from the HMAC when no length is configured
+pub const DEFAULT_TOKEN_LENGTH: usize = 8;
+
+const MIN_TOKEN_LENGTH: usize = 4;
+const MAX_TOKEN_LENGTH: usize = 64;
+
+/// Derives pseudonyms such as `[IBAN_7f3a9c21]` from an HMAC-SHA256 of the
+/// normalized value.
+///
+/// Tokens only depend on the key, the category and the value, so the same
+/// IBAN gets the same token in every document, run and machine without a
+/// shared mapping table. Retired keys can stay in the ring to recompute
+/// the tokens they produced while a new key is active.
+pub struct KeyedTokenizer {
+    keys: BTreeMap<String, Zeroizing<Vec<u8>>>,
+    active_key: String,
+    token_length: usize,
+}
+
+impl KeyedTokenizer {
+    /// Create a tokenizer with a single, active key
+    pub fn new(key_id: impl Into<String>, key: &[u8]) -> Result<Self> {
+        let key_id = key_id.into();
+        let mut tokenizer = Self {
+            keys: BTreeMap::new(),
+            active_key: key_id.clone(),
+            token_length: DEFAULT_TOKEN_LENGTH,
+        };
+        tokenizer.add_key(key_id, key)?;
+        Ok(tokenizer)
+    }
+
+    /// Build the key ring described by `[replacement.hmac]`
+    pub fn from_config(config: &HmacConfig) -> Result<Self> {
+        let active_key = config.active_key.as_deref().ok_or_else(|| AnonymizeError::ConfigError {
+            message: "replacement.hmac.active_key is required for the hash action".to_string(),
+        })?;
+
+        let mut tokenizer = Self {
+            keys: BTreeMap::new(),
+            active_key: active_key.to_string(),
+            token_length: DEFAULT_TOKEN_LENGTH,
+        };
+        for (id, source) in &config.keys {
+            let secret = source.resolve(&format!("replacement.hmac.keys.{}", id))?;
+            tokenizer.add_key(id.clone(), secret.as_bytes())?;
+        }
+        tokenizer.set_active_key(active_key)?;
+        tokenizer.set_token_length(config.token_length)?;
+        Ok(tokenizer)
+    }
+
+    /// Add a key to the ring without activating it
+    pub fn add_key(&mut self, key_id: impl Into<String>, key: &[u8]) -> Result<()> {
+        let key_id = key_id.into();
+        if key.is_empty() {
+            return Err(AnonymizeError::ConfigError {
+                message: format!("HMAC key '{}' is empty", key_id),
+            });
+        }
+        self.keys.insert(key_id, Zeroizing::new(key.to_vec()));
+        Ok(())
+    }
+
+    /// Switch token generation to another key of the ring
+    pub fn set_active_key(&mut self, key_id: &str) -> Result<()> {
+        if !self.keys.contains_key(key_id) {
+            return Err(AnonymizeError::ConfigError {
+                message: format!("Unknown HMAC key '{}'", key_id),
+            });
+        }
+        self.active_key = key_id.to_string();
+        Ok(())
+    }
+
+    /// Number of hex characters kept in each token (4 to 64).
+    ///
+    /// Short tokens are easier to read but collide sooner: with 8 characters
+    /// the odds of two values of a category sharing a token reach 1% at
+    /// about 9,000 distinct values.
+    pub fn set_token_length(&mut self, length: usize) -> Result<()> {
+        if !(MIN_TOKEN_LENGTH..=MAX_TOKEN_LENGTH).contains(&length) {
+            return Err(AnonymizeError::ConfigError {
+                message: format!(
+                    "HMAC token length must be between {} and {}, got {}",
+                    MIN_TOKEN_LENGTH, MAX_TOKEN_LENGTH, length
+                ),
+            });
+        }
+        self.token_length = length;
+        Ok(())
+    }
+
+    pub fn active_key_id(&self) -> &str {
+        &self.active_key
+    }
+
+    /// Token for a match under the active key
+    pub fn token_for(&self, m: &CandidateMatch) -> String {
+        self.token_with_key(&self.active_key, &m.category, m.entity_value())
+            .expect("BUG: active HMAC key is always in the ring")
+    }
+
+    /// Token a value had (or has) under any key of the ring, e.g. to map
+    /// tokens issued before a key rotation
+    pub fn token_with_key(&self, key_id: &str, category: &Category, value: &str) -> Option<String> {
+        let key = self.keys.get(key_id)?;
+        let cat_name = category.placeholder_name();
+
+        let mut mac = HmacSha256::new_from_slice(key)
+            .expect("BUG: HMAC accepts keys of any length");
+        mac.update(cat_name.as_bytes());
+        mac.update(&[0u8]);
+        mac.update(value.as_bytes());
+        let digest = mac.finalize().into_bytes();
+
+        let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+        Some(format!("[{}_{}]", cat_name, &hex[..self.token_length]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detector::{Confidence, Span};
+
+    fn candidate(category: Category, value: &str) -> CandidateMatch {
+        CandidateMatch {
+            span: Span { start: 0, end: value.len() },
+            detector_id: "test".to_string(),
+            category,
+            priority: 50,
+            confidence: Confidence::PatternOnly,
+            score: 0.9,
+            raw_value: value.to_string(),
+            normalized_value: Some(value.to_lowercase()),
+        }
+    }
+
+    #[test]
+    fn tokens_depend_on_key_category_and_value() {
+        let tokenizer = KeyedTokenizer::new("k1", b"secret").unwrap();
+        let token = tokenizer.token_for(&candidate(Category::Email, "ana@acme.com"));
+        assert!(token.starts_with("[EMAIL_") && token.len() == "[EMAIL_]".len() + DEFAULT_TOKEN_LENGTH, "{}", token);
+        // Same normalized value, same token, on any tokenizer with the key
+        let again = KeyedTokenizer::new("k1", b"secret").unwrap();
+        assert_eq!(again.token_for(&candidate(Category::Email, "ANA@acme.com")), token);
+
+        assert_ne!(tokenizer.token_for(&candidate(Category::Email, "bob@acme.com")), token);
+        let other_category = tokenizer.token_for(&candidate(Category::ClientName, "ana@acme.com"));
+        assert_ne!(other_category["[CLIENT_NAME_".len()..], token["[EMAIL_".len()..]);
+        let other_key = KeyedTokenizer::new("k1", b"another secret").unwrap();
+        assert_ne!(other_key.token_for(&candidate(Category::Email, "ana@acme.com")), token);
+    }
+
+    #[test]
+    fn retired_keys_still_compute_their_tokens() {
+        let mut tokenizer = KeyedTokenizer::new("2025-04", b"old").unwrap();
+        let ana = candidate(Category::Iban, "es9121000418450200051332");
+        let old_token = tokenizer.token_for(&ana);
+
+        tokenizer.add_key("2026-10", b"new").unwrap();
+        assert_eq!(tokenizer.active_key_id(), "2025-04");
+        tokenizer.set_active_key("2026-10").unwrap();
+        assert_eq!(tokenizer.active_key_id(), "2026-10");
+
+        let new_token = tokenizer.token_for(&ana);
+        assert_ne!(new_token, old_token);
+        assert_eq!(tokenizer.token_with_key("2025-04", &Category::Iban, ana.entity_value()), Some(old_token));
+        assert_eq!(tokenizer.token_with_key("2026-10", &Category::Iban, ana.entity_value()), Some(new_token));
+        assert_eq!(tokenizer.token_with_key("2024-01", &Category::Iban, ana.entity_value()), None);
+        assert!(tokenizer.set_active_key("2024-01").is_err());
+    }
+
+    #[test]
+    fn token_length_and_keys_are_validated() {
+        let mut tokenizer = KeyedTokenizer::new("k1", b"secret").unwrap();
+        assert!(tokenizer.set_token_length(MIN_TOKEN_LENGTH - 1).is_err());
+        assert!(tokenizer.set_token_length(MAX_TOKEN_LENGTH + 1).is_err());
+        tokenizer.set_token_length(MAX_TOKEN_LENGTH).unwrap();
+        assert_eq!(tokenizer.token_for(&candidate(Category::Phone, "612345678")).len(), "[PHONE_]".len() + 64);
+        assert!(KeyedTokenizer::new("k1", b"").is_err());
+
+        let config = |toml: &str| -> HmacConfig { toml::from_str(toml).unwrap() };
+        assert!(KeyedTokenizer::from_config(&config("keys.k1 = { value = \"secret\" }")).is_err());
+        assert!(KeyedTokenizer::from_config(&config("active_key = \"k2\"\nkeys.k1 = { value = \"secret\" }")).is_err());
+        assert!(KeyedTokenizer::from_config(&config("active_key = \"k1\"\ntoken_length = 2\nkeys.k1 = { value = \"secret\" }")).is_err());
+        assert!(KeyedTokenizer::from_config(&config("active_key = \"k1\"\nkeys.k1 = { value = \"secret\" }")).is_ok());
+    }
+}
@@ -0,0 +1,130 @@
+// src/replacement_engine/mask.rs
+
+use std::collections::BTreeMap;
+use crate::config::{MaskConfig, MaskRule};
+use crate::detector::{CandidateMatch, Category};
+
+/// Masks values while keeping selected characters visible, e.g.
+/// `ES** **** **** **** **** 1234` or `****@acme.com`.
+///
+/// Characters to keep are counted over letters and digits only, the same
+/// characters detectors retain in `normalized_value`, so separators such
+/// as spaces and dashes never count and are left in place.
+pub struct Masker {
+    mask_char: char,
+    rules: BTreeMap<String, MaskRule>,
+}
+
+impl Default for Masker {
+    fn default() -> Self {
+        Self::from_config(&MaskConfig::default())
+    }
+}
+
+impl Masker {
+    /// Build the masker described by `[replacement.mask]`; categories
+    /// without a rule in the file keep their built-in rule
+    pub fn from_config(config: &MaskConfig) -> Self {
+        let mut rules = default_rules();
+        for (category, rule) in &config.categories {
+            rules.insert(category.to_uppercase(), rule.clone());
+        }
+        Self {
+            mask_char: config.mask_char,
+            rules,
+        }
+    }
+
+    /// Rule applied to a category (by placeholder name, e.g. `IBAN`)
+    pub fn rule(&self, category: &str) -> MaskRule {
+        self.rules.get(category).cloned().unwrap_or_default()
+    }
+
+    /// Masked form of a match
+    pub fn mask(&self, m: &CandidateMatch) -> String {
+        self.mask_value(&m.category, &m.raw_value)
+    }
+
+    /// Masked form of a value of a category
+    pub fn mask_value(&self, category: &Category, raw: &str) -> String {
+        let rule = self.rule(&category.placeholder_name());
+
+        if rule.keep_domain {
+            if let Some(at) = raw.rfind('@') {
+                let (local, domain) = raw.split_at(at);
+                return format!("{}{}", self.mask_part(local, &rule), domain);
+            }
+        }
+        self.mask_part(raw, &rule)
+    }
+
+    fn mask_part(&self, value: &str, rule: &MaskRule) -> String {
+        let significant = value.chars().filter(|c| c.is_alphanumeric()).count();
+        let keep_last_from = significant.saturating_sub(rule.keep_last);
+
+        let mut index = 0;
+        value.chars()
+            .map(|c| {
+                if !c.is_alphanumeric() {
+                    return c;
+                }
+                let keep = index < rule.keep_first || index >= keep_last_from;
+                index += 1;
+                if keep { c } else { self.mask_char }
+            })
+            .collect()
+    }
+}
+
+fn default_rules() -> BTreeMap<String, MaskRule> {
+    let mut rules = BTreeMap::new();
+    rules.insert("IBAN".to_string(), MaskRule { keep_first: 2, keep_last: 4, keep_domain: false });
+    rules.insert("CREDIT_CARD".to_string(), MaskRule { keep_first: 0, keep_last: 4, keep_domain: false });
+    rules.insert("EMAIL".to_string(), MaskRule { keep_first: 0, keep_last: 0, keep_domain: true });
+    rules.insert("NATIONAL_ID".to_string(), MaskRule { keep_first: 0, keep_last: 4, keep_domain: false });
+    rules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn masker(toml: &str) -> Masker {
+        Masker::from_config(&toml::from_str(toml).unwrap())
+    }
+
+    #[test]
+    fn built_in_rules_keep_the_usual_characters() {
+        let masker = Masker::default();
+        assert_eq!(masker.mask_value(&Category::Iban, "ES91 2100 0418 4502 0005 1332"), "ES** **** **** **** **** 1332");
+        assert_eq!(masker.mask_value(&Category::CreditCard, "4539-1488-0343-6467"), "****-****-****-6467");
+        assert_eq!(masker.mask_value(&Category::Email, "john.doe@acme.com"), "****.***@acme.com");
+        assert_eq!(masker.mask_value(&Category::NationalId, "12345678-Z"), "*****678-Z");
+    }
+
+    #[test]
+    fn categories_without_a_rule_are_fully_masked() {
+        let masker = Masker::default();
+        assert_eq!(masker.mask_value(&Category::Phone, "+34 612 345 678"), "+** *** *** ***");
+        assert_eq!(masker.mask_value(&Category::Custom("LOT_CODE".to_string()), "LT-0042"), "**-****");
+    }
+
+    #[test]
+    fn configured_rules_override_the_built_in_ones() {
+        let masker = masker("mask_char = \"#\"\n[categories.national_id]\nkeep_last = 5\n[categories.PHONE]\nkeep_first = 2\n");
+        assert_eq!(masker.mask_value(&Category::NationalId, "12345678Z"), "####5678Z");
+        assert_eq!(masker.mask_value(&Category::Phone, "+34 612 345 678"), "+34 ### ### ###");
+        assert_eq!(masker.mask_value(&Category::Iban, "ES9121000418450200051332"), "ES##################1332");
+    }
+
+    #[test]
+    fn keep_counts_longer_than_the_value_keep_it_whole() {
+        let masker = masker("[categories.PHONE]\nkeep_first = 3\nkeep_last = 3\n");
+        assert_eq!(masker.mask_value(&Category::Phone, "91 234"), "91 234");
+    }
+
+    #[test]
+    fn addresses_without_at_are_masked_as_a_whole() {
+        assert_eq!(Masker::default().mask_value(&Category::Email, "john.doe"), "****.***");
+    }
+}
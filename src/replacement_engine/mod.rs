@@ -0,0 +1,350 @@
+// src/replacement_engine/mod.rs
+
+mod keyed;
+mod mask;
+mod synthetic;
+
+pub use keyed::{KeyedTokenizer, DEFAULT_TOKEN_LENGTH};
+pub use mask::Masker;
+pub use synthetic::SyntheticGenerator;
+
+use crate::{AnonymizeError, Result};
+use crate::config::AnonymizeConfig;
+use crate::detector::{CandidateMatch, Category, Span, DetectorId, Confidence};
+use crate::policy::{Action, Policy, PlannedReplacement};
+use chacha20poly1305::aead::{OsRng, rand_core::RngCore};
+use serde::{Serialize, Deserialize};
+use sha2::{Sha256, Digest};
+use std::collections::BTreeMap;
+
+/// Result of a substitution process
+pub struct ReplacementResult {
+    pub anonymized_text: String,
+    pub replacements: Vec<Replacement>,
+}
+
+/// Metadata about a single replacement
+pub struct Replacement {
+    pub span: Span,
+    pub original: String,
+    /// Value identifying the entity (`CandidateMatch::entity_value`)
+    pub entity_value: String,
+    pub placeholder: String,
+    pub category: Category,
+    pub detector_id: DetectorId,
+    pub confidence: Confidence,
+    pub score: f64,
+    pub action: Action,
+    /// HMAC key that produced the placeholder, for keyed tokens
+    pub key_id: Option<String>,
+}
+
+/// Placeholders assigned so far, keyed by category and entity value.
+///
+/// Identical entities (same `CandidateMatch::entity_value` in the same
+/// category) always receive the same placeholder. Entity values are stored
+/// as salted SHA-256 digests so a persisted table does not contain them in
+/// clear text. The table also records which entity each synthetic value
+/// and keyed token stands for, so two originals never share one.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PlaceholderTable {
+    salt: String,
+    counters: BTreeMap<String, usize>,
+    assigned: BTreeMap<String, String>,
+    /// Entity digest by synthetic value
+    #[serde(default)]
+    synthetic: BTreeMap<String, String>,
+    /// Entity digest by keyed token
+    #[serde(default)]
+    tokens: BTreeMap<String, String>,
+}
+
+impl Default for PlaceholderTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PlaceholderTable {
+    pub fn new() -> Self {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        Self {
+            salt: salt.iter().map(|b| format!("{:02x}", b)).collect(),
+            counters: BTreeMap::new(),
+            assigned: BTreeMap::new(),
+            synthetic: BTreeMap::new(),
+            tokens: BTreeMap::new(),
+        }
+    }
+
+    /// Number of distinct entities with a placeholder
+    pub fn len(&self) -> usize {
+        self.assigned.len()
+    }
+
+    /// Placeholder for a match, allocating the next number of its category
+    /// the first time the entity is seen
+    pub fn placeholder_for(&mut self, m: &CandidateMatch) -> String {
+        let cat_name = m.category.placeholder_name();
+        let key = self.entity_key(&cat_name, m.entity_value());
+        if let Some(placeholder) = self.assigned.get(&key) {
+            return placeholder.clone();
+        }
+
+        let counter = self.counters.entry(cat_name.clone()).or_insert(0);
+        *counter += 1;
+        let placeholder = format!("[{}_{:03}]", cat_name, counter);
+
+        self.assigned.insert(key, placeholder.clone());
+        placeholder
+    }
+
+    /// Reserve a synthetic value for the entity of a match; false when the
+    /// value already stands for another entity
+    pub fn claim_synthetic(&mut self, m: &CandidateMatch, value: &str) -> bool {
+        let key = self.entity_key(&m.category.placeholder_name(), m.entity_value());
+        claim(&mut self.synthetic, value, key)
+    }
+
+    /// Record the keyed token of a match's entity; false when the token
+    /// already stands for another entity (truncated HMACs collide)
+    pub fn claim_token(&mut self, m: &CandidateMatch, token: &str) -> bool {
+        let key = self.entity_key(&m.category.placeholder_name(), m.entity_value());
+        claim(&mut self.tokens, token, key)
+    }
+
+    fn entity_key(&self, cat_name: &str, value: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt.as_bytes());
+        hasher.update(cat_name.as_bytes());
+        hasher.update([0u8]);
+        hasher.update(value.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+/// Reserve `value` for the entity digest `key`, unless another entity has it
+fn claim(owners: &mut BTreeMap<String, String>, value: &str, key: String) -> bool {
+    match owners.get(value) {
+        Some(owner) => *owner == key,
+        None => {
+            owners.insert(value.to_string(), key);
+            true
+        }
+    }
+}
+
+/// Applies the action planned for each match.
+///
+/// Holds the machinery of every action: the keyed tokenizer and the
+/// synthetic generator need secrets and are only present when configured.
+#[derive(Default)]
+pub struct ReplacementEngine {
+    tokenizer: Option<KeyedTokenizer>,
+    synthetic: Option<SyntheticGenerator>,
+    masker: Masker,
+}
+
+impl ReplacementEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build the machinery needed by the actions of `policy`, using the
+    /// `[replacement]` settings of the configuration
+    pub fn from_config(config: &AnonymizeConfig, policy: &Policy) -> Result<Self> {
+        let mut engine = Self {
+            masker: Masker::from_config(&config.replacement.mask),
+            ..Self::default()
+        };
+        if policy.actions().any(|a| a == Action::Hash) {
+            engine.tokenizer = Some(KeyedTokenizer::from_config(&config.replacement.hmac)?);
+        }
+        if policy.actions().any(|a| a == Action::Synthesize) {
+            engine.synthetic = Some(SyntheticGenerator::from_config(&config.replacement.synthetic)?);
+        }
+        Ok(engine)
+    }
+
+    pub fn set_tokenizer(&mut self, tokenizer: KeyedTokenizer) {
+        self.tokenizer = Some(tokenizer);
+    }
+
+    pub fn set_synthetic_generator(&mut self, generator: SyntheticGenerator) {
+        self.synthetic = Some(generator);
+    }
+
+    pub fn set_masker(&mut self, masker: Masker) {
+        self.masker = masker;
+    }
+
+    /// Perform text substitution according to each match's action.
+    /// Redacted entities share the placeholder of their first occurrence,
+    /// including occurrences already recorded in `table`; hashed tokens are
+    /// the same for equal values by construction. Synthetic values are
+    /// drawn again when taken by another value in `table`, and the match is
+    /// redacted if no free value turns up. A keyed token already standing
+    /// for another value in `table` is an error. Kept matches are recorded
+    /// but left unchanged.
+    pub fn replace(
+        &self,
+        text: &str,
+        planned: Vec<PlannedReplacement>,
+        table: &mut PlaceholderTable,
+    ) -> Result<ReplacementResult> {
+        let mut replacements = Vec::new();
+
+        let mut sorted_planned = planned;
+        sorted_planned.sort_by_key(|p| p.candidate.span.start);
+
+        let mut match_data = Vec::new();
+        for mut p in sorted_planned {
+            let m = &p.candidate;
+            let (placeholder, key_id) = match p.action {
+                Action::Redact => (table.placeholder_for(m), None),
+                Action::Mask => (self.masker.mask(m), None),
+                Action::Keep => (m.raw_value.clone(), None),
+                Action::Hash => {
+                    let tokenizer = self.tokenizer.as_ref().ok_or_else(|| missing_machinery("hash", "replacement.hmac"))?;
+                    let token = tokenizer.token_for(m);
+                    if !table.claim_token(m, &token) {
+                        return Err(AnonymizeError::ConfigError {
+                            message: format!(
+                                "Two values share the keyed token {}; raise replacement.hmac.token_length",
+                                token
+                            ),
+                        });
+                    }
+                    (token, Some(tokenizer.active_key_id().to_string()))
+                }
+                Action::Synthesize => {
+                    let generator = self.synthetic.as_ref().ok_or_else(|| missing_machinery("synthesize", "replacement.synthetic"))?;
+                    match generator.distinct_value_for(m, |value| table.claim_synthetic(m, value)) {
+                        Some(value) => (value, None),
+                        // Tiny value spaces (a three-digit code) run out
+                        None => {
+                            let placeholder = table.placeholder_for(m);
+                            p.action = Action::Redact;
+                            (placeholder, None)
+                        }
+                    }
+                }
+            };
+            match_data.push((p, placeholder, key_id));
+        }
+
+        let mut anonymized_text = text.to_string();
+
+        // Reverse order replacement
+        for (p, placeholder, key_id) in match_data.into_iter().rev() {
+            let m = p.candidate;
+            let replacement = Replacement {
+                span: m.span,
+                original: m.raw_value.clone(),
+                entity_value: m.entity_value().to_string(),
+                placeholder: placeholder.clone(),
+                category: m.category,
+                detector_id: m.detector_id,
+                confidence: m.confidence,
+                score: m.score,
+                action: p.action,
+                key_id,
+            };
+
+            if p.action != Action::Keep {
+                anonymized_text.replace_range(m.span.start..m.span.end, &placeholder);
+            }
+            replacements.push(replacement);
+        }
+
+        // The replacements list in the result should be in order of appearance in the original text (for audit)
+        replacements.reverse();
+
+        Ok(ReplacementResult {
+            anonymized_text,
+            replacements,
+        })
+    }
+}
+
+fn missing_machinery(action: &str, section: &str) -> AnonymizeError {
+    AnonymizeError::ConfigError {
+        message: format!("Action '{}' is used by the policy but [{}] is not configured", action, section),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(category: Category, raw: &str) -> CandidateMatch {
+        CandidateMatch {
+            span: Span { start: 0, end: raw.len() },
+            detector_id: "test".to_string(),
+            category,
+            priority: 50,
+            confidence: Confidence::PatternOnly,
+            score: 0.9,
+            raw_value: raw.to_string(),
+            normalized_value: None,
+        }
+    }
+
+    #[test]
+    fn placeholders_are_shared_by_equal_values() {
+        let mut table = PlaceholderTable::new();
+        let john = candidate(Category::Email, "john@acme.com");
+        assert_eq!(table.placeholder_for(&john), "[EMAIL_001]");
+        assert_eq!(table.placeholder_for(&candidate(Category::Email, "ann@acme.com")), "[EMAIL_002]");
+        assert_eq!(table.placeholder_for(&john), "[EMAIL_001]");
+        assert_eq!(table.placeholder_for(&candidate(Category::Phone, "612345678")), "[PHONE_001]");
+        assert_eq!(table.len(), 3);
+    }
+
+    #[test]
+    fn synthetic_values_belong_to_one_entity() {
+        let mut table = PlaceholderTable::new();
+        let john = candidate(Category::Email, "john@acme.com");
+        let ann = candidate(Category::Email, "ann@acme.com");
+        assert!(table.claim_synthetic(&john, "laura.garcia07@example.com"));
+        assert!(table.claim_synthetic(&john, "laura.garcia07@example.com"));
+        assert!(!table.claim_synthetic(&ann, "laura.garcia07@example.com"));
+        assert!(table.claim_synthetic(&ann, "pablo.ramos12@example.com"));
+    }
+
+    #[test]
+    fn colliding_keyed_tokens_are_an_error() {
+        let mut tokenizer = KeyedTokenizer::new("k1", b"secret").unwrap();
+        tokenizer.set_token_length(4).unwrap();
+        // Two values with the same 4-character token (birthday bound: a few
+        // hundred values)
+        let mut seen = BTreeMap::new();
+        let (first, second) = (0..100_000)
+            .map(|i| format!("user{}@acme.com", i))
+            .find_map(|email| {
+                let token = tokenizer.token_for(&candidate(Category::Email, &email));
+                seen.insert(token, email.clone()).map(|previous| (previous, email))
+            })
+            .expect("4 hex characters collide within 100,000 values");
+
+        let mut engine = ReplacementEngine::new();
+        engine.set_tokenizer(tokenizer);
+        let plan = |value: &str, start: usize| PlannedReplacement {
+            candidate: CandidateMatch {
+                span: Span { start, end: start + value.len() },
+                ..candidate(Category::Email, value)
+            },
+            action: Action::Hash,
+        };
+        let text = format!("{} {}", first, second);
+        let mut table = PlaceholderTable::new();
+        let result = engine.replace(&text, vec![plan(&first, 0)], &mut table).unwrap();
+        assert_eq!(result.replacements[0].action, Action::Hash);
+
+        // Within one text, and against values tokenized earlier in the session
+        let both = vec![plan(&first, 0), plan(&second, first.len() + 1)];
+        assert!(matches!(engine.replace(&text, both, &mut PlaceholderTable::new()), Err(AnonymizeError::ConfigError { .. })));
+        assert!(engine.replace(&second, vec![plan(&second, 0)], &mut table).is_err());
+    }
+}
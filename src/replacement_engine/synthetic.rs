@@ -0,0 +1,416 @@
+// src/replacement_engine/synthetic.rs
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+use crate::{AnonymizeError, Result};
+use crate::config::SyntheticConfig;
+use crate::detector::{CandidateMatch, Category};
+use crate::utils::checksum::{iban_check_digits, luhn_check_digit, spanish_id_letter};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const FIRST_NAMES: &[&str] = &[
+    "Laura", "Carlos", "Marta", "Javier", "Lucía", "Pablo", "Elena", "Diego",
+    "Sara", "Andrés", "Nuria", "Óscar", "Clara", "Hugo", "Irene", "Raúl",
+    "Anna", "David", "Julia", "Thomas", "Emma", "Lukas", "Sophie", "Marco",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "García", "Fernández", "López", "Martín", "Sánchez", "Romero", "Navarro", "Torres",
+    "Domínguez", "Vázquez", "Ramos", "Gil", "Serrano", "Molina", "Ortega", "Delgado",
+    "Smith", "Müller", "Rossi", "Dubois", "Weber", "Bianchi", "Clarke", "Moreau",
+];
+
+const ORGANIZATION_WORDS: &[&str] = &[
+    "Atlas", "Boreal", "Cénit", "Delta", "Estela", "Faro", "Granito", "Horizonte",
+    "Íbera", "Jade", "Kappa", "Lumen", "Meridiano", "Nébula", "Orión", "Pórtico",
+];
+
+const COMPANY_SUFFIXES: &[&str] = &["S.L.", "S.A.", "Ingeniería S.L.", "Servicios S.A.", "GmbH", "Ltd."];
+
+/// Reserved domains (RFC 2606), so synthetic addresses never reach anyone
+const EMAIL_DOMAINS: &[&str] = &["example.com", "example.org", "example.net"];
+
+/// Values drawn for a match before giving up on finding a free one
+const MAX_DRAWS: usize = 64;
+
+/// Two-digit country calling codes (ITU-T E.164); `1` and `7` are the only
+/// one-digit codes, all others have three digits
+const TWO_DIGIT_COUNTRY_CODES: &[&str] = &[
+    "20", "27", "30", "31", "32", "33", "34", "36", "39", "40", "41", "43",
+    "44", "45", "46", "47", "48", "49", "51", "52", "53", "54", "55", "56",
+    "57", "58", "60", "61", "62", "63", "64", "65", "66", "81", "82", "84",
+    "86", "90", "91", "92", "93", "94", "95", "98",
+];
+
+/// Generates realistic fake values that keep the format of the original.
+///
+/// Values pass the same validators as real data: IBANs keep their country
+/// and length with valid mod-97 check digits, card numbers keep their
+/// brand prefix and a valid Luhn digit, DNI/NIE get the right control
+/// letter. Generation is seeded by an HMAC of the value under a secret
+/// seed, so reruns with the same seed produce the same output.
+pub struct SyntheticGenerator {
+    seed: Zeroizing<Vec<u8>>,
+}
+
+impl SyntheticGenerator {
+    pub fn new(seed: &[u8]) -> Result<Self> {
+        if seed.is_empty() {
+            return Err(AnonymizeError::ConfigError {
+                message: "Synthetic seed is empty".to_string(),
+            });
+        }
+        Ok(Self {
+            seed: Zeroizing::new(seed.to_vec()),
+        })
+    }
+
+    /// Build the generator described by `[replacement.synthetic]`
+    pub fn from_config(config: &SyntheticConfig) -> Result<Self> {
+        let seed = config.seed.resolve("replacement.synthetic.seed")?;
+        Self::new(seed.as_bytes())
+    }
+
+    /// Synthetic replacement for a match
+    pub fn value_for(&self, m: &CandidateMatch) -> String {
+        let mut rng = ValueRng::new(&self.seed, &m.category, m.entity_value());
+        draw(m, &mut rng)
+    }
+
+    /// Synthetic replacement accepted by `claim`, which refuses values
+    /// already standing for another original; values are drawn again,
+    /// deterministically, until one is accepted. `None` when `MAX_DRAWS`
+    /// values are refused.
+    pub fn distinct_value_for(&self, m: &CandidateMatch, mut claim: impl FnMut(&str) -> bool) -> Option<String> {
+        let mut rng = ValueRng::new(&self.seed, &m.category, m.entity_value());
+        (0..MAX_DRAWS).map(|_| draw(m, &mut rng)).find(|value| claim(value))
+    }
+}
+
+/// Next synthetic value for a match from its stream
+fn draw(m: &CandidateMatch, rng: &mut ValueRng) -> String {
+    let raw = m.raw_value.as_str();
+    match m.category {
+        Category::Iban => synthetic_iban(raw, rng),
+        Category::CreditCard => synthetic_card(raw, rng),
+        Category::NationalId if is_spanish_id(raw) => synthetic_spanish_id(raw, rng),
+        Category::Email => synthetic_email(rng),
+        Category::Phone => synthetic_phone(raw, rng),
+        Category::PersonnelName
+        | Category::RevisedBy
+        | Category::ApprovedBy
+        | Category::DesignedBy => synthetic_person(rng),
+        Category::CompanyName | Category::ClientName => format!(
+            "{} {}",
+            rng.pick(ORGANIZATION_WORDS),
+            rng.pick(COMPANY_SUFFIXES)
+        ),
+        Category::ProjectName => format!("Proyecto {}", rng.pick(ORGANIZATION_WORDS)),
+        _ => synthetic_code(raw, rng),
+    }
+}
+
+/// Deterministic byte stream: HMAC-SHA256(seed, category || value || block)
+struct ValueRng {
+    mac: HmacSha256,
+    block: u64,
+    buffer: [u8; 32],
+    position: usize,
+}
+
+impl ValueRng {
+    fn new(seed: &[u8], category: &Category, value: &str) -> Self {
+        let mut mac = HmacSha256::new_from_slice(seed)
+            .expect("BUG: HMAC accepts keys of any length");
+        mac.update(category.placeholder_name().as_bytes());
+        mac.update(&[0u8]);
+        mac.update(value.as_bytes());
+        Self {
+            mac,
+            block: 0,
+            buffer: [0u8; 32],
+            position: 32,
+        }
+    }
+
+    fn next_byte(&mut self) -> u8 {
+        if self.position == self.buffer.len() {
+            let mut mac = self.mac.clone();
+            mac.update(&self.block.to_be_bytes());
+            self.buffer.copy_from_slice(&mac.finalize().into_bytes());
+            self.block += 1;
+            self.position = 0;
+        }
+        let byte = self.buffer[self.position];
+        self.position += 1;
+        byte
+    }
+
+    /// Uniform value in `0..n` (n <= 256), by rejection sampling
+    fn below(&mut self, n: u32) -> u32 {
+        let limit = 256 - 256 % n;
+        loop {
+            let byte = self.next_byte() as u32;
+            if byte < limit {
+                return byte % n;
+            }
+        }
+    }
+
+    fn digit(&mut self) -> char {
+        char::from_digit(self.below(10), 10).expect("BUG: below(10) is a digit")
+    }
+
+    fn letter(&mut self) -> char {
+        (b'A' + self.below(26) as u8) as char
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len() as u32) as usize]
+    }
+}
+
+/// Put generated alphanumerics back into the layout (spaces, dashes) of `raw`
+fn with_layout_of(raw: &str, alphanumerics: &str) -> String {
+    let mut generated = alphanumerics.chars();
+    raw.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                generated.next().expect("BUG: layout and value lengths differ")
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+fn compact(raw: &str) -> String {
+    raw.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+fn synthetic_iban(raw: &str, rng: &mut ValueRng) -> String {
+    let cleaned = compact(raw);
+    if cleaned.len() < 5 {
+        return synthetic_code(raw, rng);
+    }
+    let country = &cleaned[..2];
+
+    // Same shape as the original BBAN: digits stay digits, letters letters
+    let bban: String = cleaned[4..]
+        .chars()
+        .map(|c| if c.is_ascii_digit() { rng.digit() } else { rng.letter() })
+        .collect();
+
+    let iban = format!("{}{}{}", country, iban_check_digits(country, &bban), bban);
+    with_layout_of(raw, &iban)
+}
+
+fn synthetic_card(raw: &str, rng: &mut ValueRng) -> String {
+    let cleaned = compact(raw);
+    if cleaned.len() < 3 || !cleaned.chars().all(|c| c.is_ascii_digit()) {
+        return synthetic_code(raw, rng);
+    }
+
+    // Keep the first two digits, which identify the brand (4x Visa,
+    // 5x MasterCard, 34/37 Amex), and recompute the Luhn digit
+    let mut payload: String = cleaned[..2].to_string();
+    for _ in 2..cleaned.len() - 1 {
+        payload.push(rng.digit());
+    }
+    let check = luhn_check_digit(&payload);
+    payload.push(char::from_digit(check, 10).expect("BUG: Luhn check digit is a digit"));
+
+    with_layout_of(raw, &payload)
+}
+
+fn is_spanish_id(raw: &str) -> bool {
+    let chars: Vec<char> = raw.chars().collect();
+    chars.len() == 9
+        && (chars[0].is_ascii_digit() || matches!(chars[0].to_ascii_uppercase(), 'X' | 'Y' | 'Z'))
+        && chars[1..8].iter().all(|c| c.is_ascii_digit())
+        && chars[8].is_ascii_alphabetic()
+}
+
+fn synthetic_spanish_id(raw: &str, rng: &mut ValueRng) -> String {
+    let first = raw.chars().next().expect("BUG: Spanish ID is not empty").to_ascii_uppercase();
+
+    let (prefix, digits) = match first {
+        // Foreigner ID: keep the X/Y/Z prefix, which counts as 0/1/2
+        'X' | 'Y' | 'Z' => (Some(first), 7),
+        _ => (None, 8),
+    };
+
+    let number_digits: String = (0..digits).map(|_| rng.digit()).collect();
+    let numeric_prefix = match prefix {
+        Some('X') => "0",
+        Some('Y') => "1",
+        Some('Z') => "2",
+        _ => "",
+    };
+    let number: u32 = format!("{}{}", numeric_prefix, number_digits)
+        .parse()
+        .expect("BUG: generated ID number is numeric");
+
+    format!(
+        "{}{}{}",
+        prefix.map(String::from).unwrap_or_default(),
+        number_digits,
+        spanish_id_letter(number)
+    )
+}
+
+fn strip_accents(s: &str) -> String {
+    use unicode_normalization::UnicodeNormalization;
+    s.nfd()
+        .filter(|c| c.is_ascii())
+        .collect()
+}
+
+fn synthetic_email(rng: &mut ValueRng) -> String {
+    let first = strip_accents(rng.pick(FIRST_NAMES)).to_lowercase();
+    let last = strip_accents(rng.pick(LAST_NAMES)).to_lowercase();
+    let number = rng.below(100);
+    let domain = rng.pick(EMAIL_DOMAINS);
+    format!("{}.{}{:02}@{}", first, last, number, domain)
+}
+
+fn synthetic_phone(raw: &str, rng: &mut ValueRng) -> String {
+    // Keep the "+CC" country code and the first national digit (mobile vs.
+    // landline); every other digit is replaced, separators are preserved
+    let country_code_digits = match raw.strip_prefix('+') {
+        Some(rest) => country_code_len(rest),
+        None => 0,
+    };
+
+    let mut digits_seen = 0;
+    raw.chars()
+        .map(|c| {
+            if !c.is_ascii_digit() {
+                return c;
+            }
+            digits_seen += 1;
+            if digits_seen <= country_code_digits + 1 {
+                c
+            } else {
+                rng.digit()
+            }
+        })
+        .collect()
+}
+
+/// Number of digits of the country code at the start of an international
+/// number (after the `+`)
+fn country_code_len(number: &str) -> usize {
+    let digits: String = number.chars().take_while(char::is_ascii_digit).take(4).collect();
+    // A separator right after the code settles it
+    if (1..=3).contains(&digits.len()) && number.len() > digits.len() {
+        return digits.len();
+    }
+    match digits.chars().next() {
+        None => 0,
+        Some('1' | '7') => 1,
+        _ if digits.get(..2).is_some_and(|code| TWO_DIGIT_COUNTRY_CODES.contains(&code)) => 2,
+        _ => 3.min(digits.len()),
+    }
+}
+
+fn synthetic_person(rng: &mut ValueRng) -> String {
+    format!("{} {}", rng.pick(FIRST_NAMES), rng.pick(LAST_NAMES))
+}
+
+/// Format-preserving code: the leading letters (the code type, e.g. `WO`
+/// or `PRJ`) and separators are kept, other letters and digits replaced
+fn synthetic_code(raw: &str, rng: &mut ValueRng) -> String {
+    let prefix_len = raw.chars().take_while(|c| c.is_alphabetic()).count();
+    raw.chars()
+        .enumerate()
+        .map(|(i, c)| {
+            if i < prefix_len {
+                c
+            } else if c.is_ascii_digit() {
+                rng.digit()
+            } else if c.is_ascii_uppercase() {
+                rng.letter()
+            } else if c.is_ascii_lowercase() {
+                rng.letter().to_ascii_lowercase()
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detector::{Confidence, Span};
+    use crate::utils::checksum::{validate_iban, validate_luhn, validate_spanish_id};
+
+    fn candidate(category: Category, raw: &str) -> CandidateMatch {
+        CandidateMatch {
+            span: Span { start: 0, end: raw.len() },
+            detector_id: "test".to_string(),
+            category,
+            priority: 50,
+            confidence: Confidence::PatternOnly,
+            score: 0.9,
+            raw_value: raw.to_string(),
+            normalized_value: None,
+        }
+    }
+
+    fn generator() -> SyntheticGenerator {
+        SyntheticGenerator::new(b"test seed").unwrap()
+    }
+
+    #[test]
+    fn values_pass_their_validators() {
+        let generator = generator();
+        let iban = generator.value_for(&candidate(Category::Iban, "ES91 2100 0418 4502 0005 1332"));
+        assert!(iban.starts_with("ES") && iban.len() == 29 && validate_iban(&iban), "{}", iban);
+        let card = generator.value_for(&candidate(Category::CreditCard, "4539-1488-0343-6467"));
+        assert!(card.starts_with("45") && validate_luhn(&card), "{}", card);
+        let nie = generator.value_for(&candidate(Category::NationalId, "X1234567L"));
+        assert!(nie.starts_with('X') && validate_spanish_id(&nie), "{}", nie);
+        assert_eq!(generator.value_for(&candidate(Category::Iban, "ES91 2100 0418 4502 0005 1332")), iban);
+    }
+
+    #[test]
+    fn phones_keep_country_code_and_first_national_digit() {
+        let generator = generator();
+        let cases = [
+            ("+34 612 345 678", "+34 6"),
+            ("+34612345678", "+346"),
+            ("+1 212 555 0100", "+1 2"),
+            ("+12125550100", "+12"),
+            ("+351 912 345 678", "+351 9"),
+            ("+351912345678", "+3519"),
+            ("612 345 678", "6"),
+        ];
+        for (raw, kept) in cases {
+            let phone = generator.value_for(&candidate(Category::Phone, raw));
+            assert!(phone.starts_with(kept), "{} -> {}", raw, phone);
+            assert_eq!(phone.chars().map(|c| c.is_ascii_digit()).collect::<Vec<_>>(),
+                raw.chars().map(|c| c.is_ascii_digit()).collect::<Vec<_>>(), "{}", raw);
+        }
+        assert_eq!(country_code_len("4930 1234"), 2);
+        assert_eq!(country_code_len("79161234567"), 1);
+        assert_eq!(country_code_len("353861234567"), 3);
+    }
+
+    #[test]
+    fn taken_values_are_drawn_again() {
+        let generator = generator();
+        let m = candidate(Category::Email, "john@acme.com");
+        let first = generator.value_for(&m);
+        let redrawn = generator.distinct_value_for(&m, |v| v != first).unwrap();
+        assert_ne!(redrawn, first);
+        assert_eq!(generator.distinct_value_for(&m, |v| v != first).unwrap(), redrawn);
+        assert_eq!(generator.distinct_value_for(&m, |_| false), None);
+    }
+}
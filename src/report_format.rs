@@ -0,0 +1,337 @@
+// src/report_format.rs
+
+use serde_json::Value;
+use crate::{AnonymizeError, Result};
+use crate::audit_report::AuditReport;
+
+/// Version of the audit report format written by this crate
+pub const REPORT_VERSION: &str = "0.2.0";
+
+/// JSON Schema (draft 2020-12) of the current report format
+pub const REPORT_SCHEMA: &str = include_str!("../schema/audit-report.schema.json");
+
+/// Parse an audit report of any known version, upgrading it to the
+/// current format.
+///
+/// Upgrading rewrites fields, so the signature of an upgraded report no
+/// longer matches it: check signatures on the report as stored, with
+/// `ReportSigner::check_signature_json`.
+pub fn read_report(json: &str) -> Result<AuditReport> {
+    let value: Value = serde_json::from_str(json).map_err(|e| report_error(format!("Invalid JSON: {}", e)))?;
+    read_report_value(value)
+}
+
+/// `read_report` for a report already parsed as JSON
+pub fn read_report_value(report: Value) -> Result<AuditReport> {
+    serde_json::from_value(upgrade_report(report)?)
+        .map_err(|e| report_error(format!("Invalid report: {}", e)))
+}
+
+/// Convert a report in JSON form to the current format
+pub fn upgrade_report(mut report: Value) -> Result<Value> {
+    let version = report.get("version")
+        .and_then(Value::as_str)
+        .ok_or_else(|| report_error("Report has no version".to_string()))?
+        .to_string();
+
+    match version.as_str() {
+        REPORT_VERSION => {}
+        "0.1.0" => upgrade_0_1_0(&mut report),
+        _ => return Err(report_error(format!(
+            "Unsupported report version '{}' (this build reads up to {})",
+            version, REPORT_VERSION
+        ))),
+    }
+    Ok(report)
+}
+
+/// 0.1.0 wrote categories and confidences as Rust debug names (`NationalId`,
+/// `Custom("client")`, `PatternOnly`) and had no disclosure level: its
+/// records held the original values in full. Each record covered a single
+/// `original_span` and had no action (values were always redacted) nor
+/// score, written as 0. It also lacked the filtering counters, and early
+/// reports have no conflicts
+fn upgrade_0_1_0(report: &mut Value) {
+    let Some(object) = report.as_object_mut() else {
+        return;
+    };
+    object.insert("version".to_string(), Value::from(REPORT_VERSION));
+    object.entry("disclosure").or_insert_with(|| Value::from("full"));
+    object.entry("conflicts").or_insert_with(|| Value::Array(Vec::new()));
+
+    // Filtering counters came with 0.2.0; nothing was filtered before
+    if let Some(Value::Object(statistics)) = object.get_mut("statistics") {
+        for counter in ["suppressed_by_allowlist", "discarded_by_context", "below_threshold"] {
+            statistics.entry(counter).or_insert_with(|| Value::from(0));
+        }
+    }
+
+    if let Some(Value::Object(counts)) = object.get_mut("statistics").and_then(|s| s.get_mut("matches_by_category")) {
+        let legacy = std::mem::take(counts);
+        for (category, count) in legacy {
+            let name = legacy_category(&Value::from(category));
+            let total = counts.get(&name).and_then(Value::as_u64).unwrap_or(0) + count.as_u64().unwrap_or(0);
+            counts.insert(name, Value::from(total));
+        }
+    }
+
+    for record in object.get_mut("replacements").and_then(Value::as_array_mut).into_iter().flatten() {
+        upgrade_typed_fields(record);
+        let Some(record) = record.as_object_mut() else {
+            continue;
+        };
+        if let Some(span) = record.remove("original_span") {
+            record.entry("occurrences").or_insert_with(|| Value::Array(vec![span]));
+        }
+        record.entry("action").or_insert_with(|| Value::from("redact"));
+        record.entry("score").or_insert_with(|| Value::from(0.0));
+    }
+    // Both findings of each conflict
+    for conflict in object.get_mut("conflicts").and_then(Value::as_array_mut).into_iter().flatten() {
+        for finding in conflict.as_object_mut().into_iter().flat_map(|c| c.values_mut()) {
+            upgrade_typed_fields(finding);
+        }
+    }
+}
+
+fn upgrade_typed_fields(item: &mut Value) {
+    if let Some(category) = item.get_mut("category") {
+        *category = Value::from(legacy_category(category));
+    }
+    if let Some(confidence) = item.get_mut("confidence") {
+        if let Some(name) = confidence.as_str() {
+            *confidence = Value::from(snake_case(name).to_lowercase());
+        }
+    }
+}
+
+/// Placeholder name of a category written as a debug string (`NationalId`,
+/// `Custom("client")`) or with the former derived representation
+/// (`{"Custom": "client"}`)
+fn legacy_category(value: &Value) -> String {
+    if let Some(custom) = value.get("Custom").and_then(Value::as_str) {
+        return custom.to_uppercase();
+    }
+    let name = value.as_str().unwrap_or_default();
+    match name.strip_prefix("Custom(\"").and_then(|n| n.strip_suffix("\")")) {
+        Some(custom) => custom.to_uppercase(),
+        None => snake_case(name).to_uppercase(),
+    }
+}
+
+/// `NationalId` → `National_Id`
+fn snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, c) in name.char_indices() {
+        if i > 0 && c.is_uppercase() {
+            snake.push('_');
+        }
+        snake.push(c);
+    }
+    snake
+}
+
+fn report_error(message: String) -> AnonymizeError {
+    AnonymizeError::ReportError { message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Action, Anonymizer, AnonymizeConfig, Category, Confidence};
+
+    const TEXT: &str = "Mail ana@example.com or 612 345 678, DNI 12345678Z, ref C-2041";
+
+    const CUSTOM: &str = "[[custom_detectors]]\nid = \"codes\"\npattern = \"C-\\\\d+\"\ncategory = \"client_code\"\n";
+
+    fn schema() -> Value {
+        serde_json::from_str(REPORT_SCHEMA).unwrap()
+    }
+
+    /// Check `value` against the subset of JSON Schema the report schema
+    /// uses, collecting one message per violation
+    fn validate(schema: &Value, root: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let name = reference.strip_prefix("#/$defs/").expect("only local references");
+            return validate(&root["$defs"][name], root, value, path, errors);
+        }
+        let mut fail = |message: String| errors.push(format!("{}: {}", path, message));
+        if let Some(expected) = schema.get("const") {
+            if value != expected {
+                fail(format!("{} is not {}", value, expected));
+            }
+        }
+        if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+            if !options.contains(value) {
+                fail(format!("{} is not one of {:?}", value, options));
+            }
+        }
+        if let Some(kind) = schema.get("type").and_then(Value::as_str) {
+            let matches = match kind {
+                "object" => value.is_object(),
+                "array" => value.is_array(),
+                "string" => value.is_string(),
+                "integer" => value.is_u64() || value.is_i64(),
+                "number" => value.is_number(),
+                other => panic!("unsupported type {}", other),
+            };
+            if !matches {
+                fail(format!("{} is not of type {}", value, kind));
+            }
+        }
+        if let (Some(pattern), Some(text)) = (schema.get("pattern").and_then(Value::as_str), value.as_str()) {
+            if !regex::Regex::new(pattern).unwrap().is_match(text) {
+                fail(format!("'{}' does not match {}", text, pattern));
+            }
+        }
+        if let Some(number) = value.as_f64() {
+            if schema.get("minimum").and_then(Value::as_f64).is_some_and(|min| number < min)
+                || schema.get("maximum").and_then(Value::as_f64).is_some_and(|max| number > max)
+            {
+                fail(format!("{} is out of range", number));
+            }
+        }
+        for field in schema.get("required").and_then(Value::as_array).into_iter().flatten() {
+            let field = field.as_str().unwrap();
+            if value.get(field).is_none() {
+                fail(format!("missing '{}'", field));
+            }
+        }
+        if let Some(object) = value.as_object() {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (key, item) in object {
+                let item_path = format!("{}.{}", path, key);
+                match (properties.and_then(|p| p.get(key)), schema.get("additionalProperties")) {
+                    (Some(property), _) => validate(property, root, item, &item_path, errors),
+                    (None, Some(additional)) => validate(additional, root, item, &item_path, errors),
+                    (None, None) if properties.is_some() => errors.push(format!("{}: unexpected field", item_path)),
+                    (None, None) => {}
+                }
+            }
+        }
+        if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+            for (i, item) in array.iter().enumerate() {
+                validate(items, root, item, &format!("{}[{}]", path, i), errors);
+            }
+        }
+    }
+
+    fn schema_errors(report: &Value) -> Vec<String> {
+        let schema = schema();
+        let mut errors = Vec::new();
+        validate(&schema, &schema, report, "$", &mut errors);
+        errors
+    }
+
+    fn report_json(toml: &str) -> Value {
+        let config = AnonymizeConfig::from_toml_str(&format!("{}{}", CUSTOM, toml)).unwrap();
+        let output = Anonymizer::from_config(&config).unwrap().anonymize(TEXT).unwrap();
+        serde_json::to_value(&output.report).unwrap()
+    }
+
+    #[test]
+    fn reports_match_the_schema_at_every_disclosure_level() {
+        let levels = [
+            ("none", "original_value", false),
+            ("hash", "original_hash", true),
+            ("masked", "original_value", true),
+            ("full", "original_value", true),
+        ];
+        for (level, field, disclosed) in levels {
+            let salt = if level == "hash" { "salt = { value = \"pepper\" }\n" } else { "" };
+            let report = report_json(&format!(
+                "[policy.categories]\nPHONE = \"keep\"\n[audit]\ndisclosure = \"{}\"\n{}[audit.signing]\nactive_key = \"k1\"\nkeys.k1 = {{ value = \"secret\" }}\n",
+                level, salt
+            ));
+            assert_eq!(schema_errors(&report), Vec::<String>::new(), "{}", level);
+            assert_eq!(report["disclosure"], level);
+            assert!(report["signature"].is_object(), "{}", level);
+            let records = report["replacements"].as_array().unwrap();
+            assert!(records.len() >= 4, "{}", level);
+            assert!(records.iter().all(|r| r.get(field).is_some() == disclosed), "{}", level);
+        }
+    }
+
+    #[test]
+    fn schema_catches_invalid_reports() {
+        let mut report = report_json("");
+        assert!(schema_errors(&report).is_empty());
+        report["disclosure"] = Value::from("partial");
+        report["replacements"][0]["confidence"] = Value::from("PatternOnly");
+        report.as_object_mut().unwrap().remove("input_hash");
+        assert_eq!(schema_errors(&report).len(), 3);
+    }
+
+    /// Report written by 0.1.0 for
+    /// "DNI 12345678Z, mail ana@example.com, IBAN ES91 2100 0418 4502 0005 1332, again ana@example.com"
+    const REPORT_0_1_0: &str = r#"{
+        "version": "0.1.0",
+        "timestamp": "2026-10-18T01:10:43.415770275Z",
+        "input_hash": "3d038ce38e2c07602dcfaecc891deb22f38cb01bc2afa0cf74e371e3cfdb70d7",
+        "config_hash": "default",
+        "statistics": {
+            "total_matches": 4,
+            "matches_by_category": {"NationalId": 1, "Email": 2, "Iban": 1},
+            "conflicts_resolved": 0,
+            "processing_time_ms": 3
+        },
+        "replacements": [
+            {"placeholder": "[NATIONAL_ID_001]", "category": "NationalId", "detector_id": "spanish_id",
+             "confidence": "Verified", "original_span": {"start": 4, "end": 13}, "original_value": "12345678Z"},
+            {"placeholder": "[EMAIL_001]", "category": "Email", "detector_id": "email",
+             "confidence": "PatternOnly", "original_span": {"start": 20, "end": 35}, "original_value": "ana@example.com"},
+            {"placeholder": "[IBAN_001]", "category": "Iban", "detector_id": "iban",
+             "confidence": "Verified", "original_span": {"start": 42, "end": 71},
+             "original_value": "ES91 2100 0418 4502 0005 1332"},
+            {"placeholder": "[EMAIL_002]", "category": "Email", "detector_id": "email",
+             "confidence": "PatternOnly", "original_span": {"start": 79, "end": 94}, "original_value": "ana@example.com"}
+        ]
+    }"#;
+
+    #[test]
+    fn version_0_1_0_reports_are_upgraded() {
+        let legacy: Value = serde_json::from_str(REPORT_0_1_0).unwrap();
+        let upgraded = upgrade_report(legacy).unwrap();
+        assert_eq!(schema_errors(&upgraded), Vec::<String>::new());
+        assert_eq!(upgraded["version"], REPORT_VERSION);
+        assert_eq!(upgraded["disclosure"], "full");
+        assert_eq!(upgraded["conflicts"], serde_json::json!([]));
+        assert_eq!(upgraded["statistics"]["matches_by_category"], serde_json::json!({"NATIONAL_ID": 1, "EMAIL": 2, "IBAN": 1}));
+
+        let report = read_report(REPORT_0_1_0).unwrap();
+        let records: Vec<_> = report.replacements.iter()
+            .map(|r| (r.placeholder.as_deref().unwrap(), r.category.clone(), r.confidence, r.occurrences[0].start, r.action))
+            .collect();
+        assert_eq!(records, [
+            ("[NATIONAL_ID_001]", Category::NationalId, Confidence::Verified, 4, Action::Redact),
+            ("[EMAIL_001]", Category::Email, Confidence::PatternOnly, 20, Action::Redact),
+            ("[IBAN_001]", Category::Iban, Confidence::Verified, 42, Action::Redact),
+            ("[EMAIL_002]", Category::Email, Confidence::PatternOnly, 79, Action::Redact),
+        ]);
+        assert!(report.replacements.iter().all(|r| r.occurrences.len() == 1 && r.score == 0.0));
+        assert_eq!(report.replacements[2].original_value.as_deref(), Some("ES91 2100 0418 4502 0005 1332"));
+    }
+
+    #[test]
+    fn legacy_custom_categories_are_upper_cased() {
+        assert_eq!(legacy_category(&Value::from("Custom(\"client\")")), "CLIENT");
+        assert_eq!(legacy_category(&serde_json::json!({"Custom": "email_alias"})), "EMAIL_ALIAS");
+        assert_eq!(legacy_category(&Value::from("CreditCard")), "CREDIT_CARD");
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        assert!(read_report(r#"{"version": "9.0.0"}"#).is_err());
+        assert!(read_report(r#"{"timestamp": "2024-12-27T10:30:00Z"}"#).is_err());
+    }
+
+    #[test]
+    fn custom_categories_round_trip() {
+        let category = Category::from_placeholder_name("client_code");
+        assert_eq!(category, Category::Custom("CLIENT_CODE".to_string()));
+        let json = serde_json::to_string(&category).unwrap();
+        assert_eq!(json, "\"CLIENT_CODE\"");
+        assert_eq!(serde_json::from_str::<Category>(&json).unwrap(), category);
+        assert_eq!(serde_json::from_str::<Category>("\"national_id\"").unwrap(), Category::NationalId);
+    }
+}
@@ -0,0 +1,62 @@
+// src/scan.rs
+
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use crate::audit_report::{ConflictRecord, DetectorStatistics, StageTimings};
+use crate::detector::{CandidateMatch, Category, Confidence, Span};
+
+/// Inventory of the sensitive data found in a text, produced by
+/// `Anonymizer::scan` without modifying the text.
+///
+/// Findings carry their location and classification only; the values
+/// themselves stay in the scanned document.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ScanReport {
+    pub timestamp: DateTime<Utc>,
+    pub input_hash: String,
+    pub config_hash: String,
+    /// Matches kept after conflict resolution, in text order
+    pub findings: Vec<Finding>,
+    /// Candidates the conflict resolver discarded, with the match each
+    /// lost to, when requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discarded: Option<Vec<ConflictRecord>>,
+    /// Candidates dropped because their value is allowlisted
+    pub suppressed_by_allowlist: usize,
+    /// Candidates dropped by a discard keyword nearby
+    pub discarded_by_context: usize,
+    /// Candidates dropped for scoring below their detector's `min_score`
+    pub below_threshold: usize,
+    pub processing_time_ms: u64,
+    /// Microseconds spent in each stage run (no policy nor replacement)
+    #[serde(default)]
+    pub timings: StageTimings,
+    /// Counts and time by detector id; `accepted` counts findings
+    #[serde(default)]
+    pub detectors: BTreeMap<String, DetectorStatistics>,
+}
+
+/// A detected value, located by its byte span in the input
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Finding {
+    pub span: Span,
+    pub category: Category,
+    pub detector_id: String,
+    pub confidence: Confidence,
+    pub score: f64,
+    pub priority: u32,
+}
+
+impl From<&CandidateMatch> for Finding {
+    fn from(m: &CandidateMatch) -> Self {
+        Self {
+            span: m.span,
+            category: m.category.clone(),
+            detector_id: m.detector_id.clone(),
+            confidence: m.confidence,
+            score: m.score,
+            priority: m.priority,
+        }
+    }
+}
@@ -0,0 +1,86 @@
+// src/session.rs
+
+use chacha20poly1305::aead::{OsRng, rand_core::RngCore};
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use std::path::Path;
+use crate::{AnonymizeError, Result};
+use crate::replacement_engine::PlaceholderTable;
+
+/// Placeholder numbering shared by several anonymization calls.
+///
+/// Documents anonymized in the same session use the same placeholder for
+/// the same value, so `[PERSONNEL_NAME_004]` designates one person across
+/// a whole delivery package. Sessions can be saved and reloaded to extend
+/// a package later. The saved file contains placeholders and salted digests
+/// of the values rather than the values, but the salt is saved with them:
+/// values from small spaces (IDs, phone numbers) can be found by trying
+/// every candidate, so session files need the protection of the documents.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AnonymizationSession {
+    id: String,
+    created_at: DateTime<Utc>,
+    documents_processed: usize,
+    table: PlaceholderTable,
+}
+
+impl Default for AnonymizationSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnonymizationSession {
+    pub fn new() -> Self {
+        let mut id = [0u8; 8];
+        OsRng.fill_bytes(&mut id);
+        Self {
+            id: id.iter().map(|b| format!("{:02x}", b)).collect(),
+            created_at: Utc::now(),
+            documents_processed: 0,
+            table: PlaceholderTable::new(),
+        }
+    }
+
+    /// Identifier recorded in the audit report of every document
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    pub fn documents_processed(&self) -> usize {
+        self.documents_processed
+    }
+
+    /// Number of distinct values with an assigned placeholder
+    pub fn entity_count(&self) -> usize {
+        self.table.len()
+    }
+
+    /// Write the session to a JSON file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .expect("BUG: session is always serializable");
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Read a session previously written with `save`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        serde_json::from_str(&json).map_err(|e| AnonymizeError::ConfigError {
+            message: format!("Invalid session file: {}", e),
+        })
+    }
+
+    pub(crate) fn table_mut(&mut self) -> &mut PlaceholderTable {
+        &mut self.table
+    }
+
+    pub(crate) fn record_document(&mut self) {
+        self.documents_processed += 1;
+    }
+}
@@ -0,0 +1,322 @@
+// src/signing.rs
+
+use hmac::{Hmac, Mac};
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use sha2::{Sha256, Digest};
+use std::collections::BTreeMap;
+use zeroize::Zeroizing;
+use crate::{AnonymizeError, Result};
+use crate::audit_report::AuditReport;
+use crate::config::SigningConfig;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Algorithm recorded in signatures produced by `ReportSigner`
+pub const SIGNATURE_ALGORITHM: &str = "HMAC-SHA256";
+
+/// Signature binding an audit report to its input, configuration and
+/// output
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReportSignature {
+    pub algorithm: String,
+    /// Id of the signing key in the key ring
+    pub key_id: String,
+    /// Hex-encoded MAC of the canonical report
+    pub value: String,
+}
+
+/// Signs audit reports with an HMAC key and verifies them later.
+///
+/// The MAC covers the canonical serialization of the whole report except
+/// its signature: JSON with object keys sorted, which includes
+/// `input_hash`, `config_hash` and `output_hash`. Any edit to the report,
+/// or a report paired with another input or output, fails verification.
+/// Retired keys can stay in the ring to verify the reports they signed.
+pub struct ReportSigner {
+    keys: BTreeMap<String, Zeroizing<Vec<u8>>>,
+    active_key: String,
+}
+
+/// Outcome of checking a report's signature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureCheck {
+    Valid,
+    /// The report or its signature was altered, or signed with another key
+    Invalid,
+    /// The report carries no signature
+    Missing,
+    /// The signing key is not in the ring
+    UnknownKey(String),
+}
+
+/// Outcome of checking a report against an original and an anonymized text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verification {
+    pub signature: SignatureCheck,
+    /// The original hashes to the report's `input_hash`
+    pub input_matches: bool,
+    /// The anonymized text hashes to the report's `output_hash`
+    pub output_matches: bool,
+}
+
+impl Verification {
+    /// The report is authentic and describes these exact files
+    pub fn is_valid(&self) -> bool {
+        self.signature == SignatureCheck::Valid && self.input_matches && self.output_matches
+    }
+}
+
+impl ReportSigner {
+    /// Create a signer with a single, active key
+    pub fn new(key_id: impl Into<String>, key: &[u8]) -> Result<Self> {
+        let key_id = key_id.into();
+        let mut signer = Self {
+            keys: BTreeMap::new(),
+            active_key: key_id.clone(),
+        };
+        signer.add_key(key_id, key)?;
+        Ok(signer)
+    }
+
+    /// Build the key ring described by `[audit.signing]`, or `None` when
+    /// no key is configured
+    pub fn from_config(config: &SigningConfig) -> Result<Option<Self>> {
+        let Some(active_key) = config.active_key.as_deref() else {
+            if config.keys.is_empty() {
+                return Ok(None);
+            }
+            return Err(AnonymizeError::ConfigError {
+                message: "audit.signing.active_key is required when signing keys are configured".to_string(),
+            });
+        };
+
+        let mut signer = Self {
+            keys: BTreeMap::new(),
+            active_key: active_key.to_string(),
+        };
+        for (id, source) in &config.keys {
+            let secret = source.resolve(&format!("audit.signing.keys.{}", id))?;
+            signer.add_key(id.clone(), secret.as_bytes())?;
+        }
+        signer.set_active_key(active_key)?;
+        Ok(Some(signer))
+    }
+
+    /// Add a key to the ring without activating it
+    pub fn add_key(&mut self, key_id: impl Into<String>, key: &[u8]) -> Result<()> {
+        let key_id = key_id.into();
+        if key.is_empty() {
+            return Err(AnonymizeError::ConfigError {
+                message: format!("Signing key '{}' is empty", key_id),
+            });
+        }
+        self.keys.insert(key_id, Zeroizing::new(key.to_vec()));
+        Ok(())
+    }
+
+    /// Sign new reports with another key of the ring
+    pub fn set_active_key(&mut self, key_id: &str) -> Result<()> {
+        if !self.keys.contains_key(key_id) {
+            return Err(AnonymizeError::ConfigError {
+                message: format!("Unknown signing key '{}'", key_id),
+            });
+        }
+        self.active_key = key_id.to_string();
+        Ok(())
+    }
+
+    /// Sign a report with the active key, replacing any previous signature
+    pub fn sign(&self, report: &mut AuditReport) {
+        report.signature = None;
+        let value = self.mac(&self.active_key, &canonical_json(report))
+            .expect("BUG: active signing key is always in the ring");
+        report.signature = Some(ReportSignature {
+            algorithm: SIGNATURE_ALGORITHM.to_string(),
+            key_id: self.active_key.clone(),
+            value,
+        });
+    }
+
+    /// Check that a report is unaltered and signed by a key of the ring
+    pub fn check_signature(&self, report: &AuditReport) -> SignatureCheck {
+        self.check_signature_json(&to_json(report))
+    }
+
+    /// Check the signature of a report as stored, in any format version
+    pub fn check_signature_json(&self, report: &Value) -> SignatureCheck {
+        let Some(signature) = report.get("signature").filter(|s| !s.is_null()) else {
+            return SignatureCheck::Missing;
+        };
+        let Ok(signature) = serde_json::from_value::<ReportSignature>(signature.clone()) else {
+            return SignatureCheck::Invalid;
+        };
+        if signature.algorithm != SIGNATURE_ALGORITHM {
+            return SignatureCheck::Invalid;
+        }
+        let Some(key) = self.keys.get(&signature.key_id) else {
+            return SignatureCheck::UnknownKey(signature.key_id);
+        };
+        let Ok(expected) = decode_hex(&signature.value) else {
+            return SignatureCheck::Invalid;
+        };
+
+        let mut unsigned = report.clone();
+        if let Some(object) = unsigned.as_object_mut() {
+            object.remove("signature");
+        }
+        let mut mac = HmacSha256::new_from_slice(key)
+            .expect("BUG: HMAC accepts keys of any length");
+        mac.update(&canonical_json(&unsigned));
+        // Constant-time comparison
+        match mac.verify_slice(&expected) {
+            Ok(()) => SignatureCheck::Valid,
+            Err(_) => SignatureCheck::Invalid,
+        }
+    }
+
+    /// Check a report against the text given to the engine and the text
+    /// it returned
+    pub fn verify(&self, report: &AuditReport, original: &[u8], anonymized: &[u8]) -> Verification {
+        self.verify_json(&to_json(report), original, anonymized)
+    }
+
+    /// `verify` on a report as stored, in any format version
+    pub fn verify_json(&self, report: &Value, original: &[u8], anonymized: &[u8]) -> Verification {
+        let hash = |pointer: &str| report.pointer(pointer).and_then(Value::as_str).map(str::to_string);
+        Verification {
+            signature: self.check_signature_json(report),
+            input_matches: hash("/input_hash") == Some(format!("{:x}", Sha256::digest(original))),
+            output_matches: hash("/output_hash/value") == Some(format!("{:x}", Sha256::digest(anonymized))),
+        }
+    }
+
+    fn mac(&self, key_id: &str, message: &[u8]) -> Option<String> {
+        let key = self.keys.get(key_id)?;
+        let mut mac = HmacSha256::new_from_slice(key)
+            .expect("BUG: HMAC accepts keys of any length");
+        mac.update(message);
+        let digest = mac.finalize().into_bytes();
+        Some(digest.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+}
+
+fn to_json(report: &AuditReport) -> Value {
+    serde_json::to_value(report).expect("BUG: audit reports serialize to JSON")
+}
+
+/// JSON with object keys sorted, so the bytes signed or hashed do not
+/// depend on field order or map iteration order
+pub(crate) fn canonical_json<T: Serialize>(value: &T) -> Vec<u8> {
+    // `serde_json::Value` keeps object keys in a sorted map
+    let value = serde_json::to_value(value).expect("BUG: reports serialize to JSON");
+    serde_json::to_vec(&value).expect("BUG: JSON values serialize")
+}
+
+fn decode_hex(hex: &str) -> std::result::Result<Vec<u8>, ()> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return Err(());
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| ()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Anonymizer, AnonymizeConfig};
+
+    const TEXT: &str = "Contact ana@example.com, DNI 12345678Z";
+
+    fn signed(signer: &ReportSigner) -> (AuditReport, String) {
+        let anonymizer = Anonymizer::from_config(&AnonymizeConfig::from_toml_str("").unwrap()).unwrap();
+        let output = anonymizer.anonymize(TEXT).unwrap();
+        let mut report = output.report;
+        signer.sign(&mut report);
+        (report, output.text)
+    }
+
+    #[test]
+    fn signed_reports_verify_against_their_texts() {
+        let signer = ReportSigner::new("k1", b"secret").unwrap();
+        let (report, text) = signed(&signer);
+        let signature = report.signature.as_ref().unwrap();
+        assert_eq!((signature.algorithm.as_str(), signature.key_id.as_str()), (SIGNATURE_ALGORITHM, "k1"));
+        assert!(signer.verify(&report, TEXT.as_bytes(), text.as_bytes()).is_valid());
+
+        let other = signer.verify(&report, b"another input", text.as_bytes());
+        assert_eq!(other.signature, SignatureCheck::Valid);
+        assert!(!other.input_matches && other.output_matches && !other.is_valid());
+        assert!(!signer.verify(&report, TEXT.as_bytes(), TEXT.as_bytes()).output_matches);
+    }
+
+    #[test]
+    fn any_edit_breaks_the_signature() {
+        let signer = ReportSigner::new("k1", b"secret").unwrap();
+        let (report, _) = signed(&signer);
+        let stored = to_json(&report);
+        assert_eq!(signer.check_signature_json(&stored), SignatureCheck::Valid);
+
+        let mut edited = stored.clone();
+        edited["replacements"][0]["occurrences"][0]["start"] = Value::from(1);
+        assert_eq!(signer.check_signature_json(&edited), SignatureCheck::Invalid);
+
+        let mut edited = stored.clone();
+        edited["input_hash"] = Value::from("0".repeat(64));
+        assert_eq!(signer.check_signature_json(&edited), SignatureCheck::Invalid);
+
+        let mut edited = stored.clone();
+        edited["signature"]["value"] = Value::from("00".repeat(32));
+        assert_eq!(signer.check_signature_json(&edited), SignatureCheck::Invalid);
+
+        let mut edited = stored.clone();
+        edited["signature"]["value"] = Value::from("not hex");
+        assert_eq!(signer.check_signature_json(&edited), SignatureCheck::Invalid);
+
+        let mut edited = stored;
+        edited["signature"]["algorithm"] = Value::from("HMAC-SHA1");
+        assert_eq!(signer.check_signature_json(&edited), SignatureCheck::Invalid);
+    }
+
+    #[test]
+    fn field_order_does_not_matter() {
+        let signer = ReportSigner::new("k1", b"secret").unwrap();
+        let (report, _) = signed(&signer);
+        let json = serde_json::to_string_pretty(&report).unwrap();
+        // Parsing sorts keys; the signature covers content, not layout
+        let reparsed: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(signer.check_signature_json(&reparsed), SignatureCheck::Valid);
+    }
+
+    #[test]
+    fn keys_are_checked_by_id() {
+        let signer = ReportSigner::new("k1", b"secret").unwrap();
+        let (mut report, _) = signed(&signer);
+
+        let mut rotated = ReportSigner::new("k2", b"new secret").unwrap();
+        assert_eq!(rotated.check_signature(&report), SignatureCheck::UnknownKey("k1".to_string()));
+        rotated.add_key("k1", b"secret").unwrap();
+        assert_eq!(rotated.check_signature(&report), SignatureCheck::Valid);
+
+        let impostor = ReportSigner::new("k1", b"guess").unwrap();
+        assert_eq!(impostor.check_signature(&report), SignatureCheck::Invalid);
+
+        report.signature = None;
+        assert_eq!(signer.check_signature(&report), SignatureCheck::Missing);
+    }
+
+    #[test]
+    fn key_rings_are_validated() {
+        assert!(ReportSigner::new("k1", b"").is_err());
+        let mut signer = ReportSigner::new("k1", b"secret").unwrap();
+        assert!(signer.set_active_key("k2").is_err());
+
+        let config = |toml: &str| -> SigningConfig { toml::from_str(toml).unwrap() };
+        assert!(ReportSigner::from_config(&config("")).unwrap().is_none());
+        assert!(ReportSigner::from_config(&config("keys.k1 = { value = \"secret\" }")).is_err());
+        assert!(ReportSigner::from_config(&config("active_key = \"k2\"\nkeys.k1 = { value = \"secret\" }")).is_err());
+        assert!(ReportSigner::from_config(&config("active_key = \"k1\"\nkeys.k1 = { value = \"secret\" }")).unwrap().is_some());
+    }
+}
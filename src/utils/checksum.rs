@@ -1,9 +1,10 @@
 // src/utils/checksum.rs
 
-/// IBAN (ISO 7064 Mod 97-10)
+/// IBAN (ISO 7064 Mod 97-10); characters other than ASCII letters and
+/// digits are ignored
 pub fn validate_iban(iban: &str) -> bool {
     let cleaned: String = iban.chars()
-        .filter(|c| c.is_alphanumeric())
+        .filter(|c| c.is_ascii_alphanumeric())
         .collect();
     
     if cleaned.len() < 5 {
@@ -13,35 +14,46 @@ pub fn validate_iban(iban: &str) -> bool {
     // Move first 4 chars to end
     let rearranged = format!("{}{}", &cleaned[4..], &cleaned[..4]);
     
+    // Mod 97 check
+    mod97(&rearranged) == 1
+}
+
+/// IBAN check digits for a country code and BBAN (ISO 7064 Mod 97-10)
+pub fn iban_check_digits(country: &str, bban: &str) -> String {
+    let rearranged = format!("{}{}00", bban, country);
+    format!("{:02}", 98 - mod97(&rearranged))
+}
+
+/// Remainder modulo 97 of an alphanumeric string, letters counting as
+/// two digits (A=10, B=11, ...)
+fn mod97(alphanumeric: &str) -> u64 {
     // Convert letters to numbers (A=10, B=11, ...)
-    let numeric: String = rearranged.chars()
+    let numeric: String = alphanumeric.chars()
         .map(|c| {
             if c.is_ascii_digit() {
                 c.to_string()
             } else {
-                ((c as u32) - ('A' as u32) + 10).to_string()
+                ((c.to_ascii_uppercase() as u32) - ('A' as u32) + 10).to_string()
             }
         })
         .collect();
     
-    // Mod 97 check
-    let remainder = numeric
+    numeric
         .chars()
         .fold(0u64, |acc, c| {
             (acc * 10 + c.to_digit(10).unwrap() as u64) % 97
-        });
-    
-    remainder == 1
+        })
 }
 
+/// Control letters of Spanish IDs, indexed by number mod 23
+const SPANISH_ID_LETTERS: &[char] = &[
+    'T', 'R', 'W', 'A', 'G', 'M', 'Y', 'F', 'P', 'D',
+    'X', 'B', 'N', 'J', 'Z', 'S', 'Q', 'V', 'H', 'L',
+    'C', 'K', 'E'
+];
+
 /// Spanish ID (DNI/NIE)
 pub fn validate_spanish_id(id: &str) -> bool {
-    const LETTERS: &[char] = &[
-        'T', 'R', 'W', 'A', 'G', 'M', 'Y', 'F', 'P', 'D',
-        'X', 'B', 'N', 'J', 'Z', 'S', 'Q', 'V', 'H', 'L',
-        'C', 'K', 'E'
-    ];
-    
     let chars: Vec<char> = id.chars().collect();
     if chars.len() != 9 {
         return false;
@@ -72,8 +84,12 @@ pub fn validate_spanish_id(id: &str) -> bool {
         Err(_) => return false,
     };
     
-    let expected_letter = LETTERS[(number % 23) as usize];
-    chars[8].to_ascii_uppercase() == expected_letter
+    chars[8].to_ascii_uppercase() == spanish_id_letter(number)
+}
+
+/// Control letter of a Spanish ID number (NIE prefixes X/Y/Z count as 0/1/2)
+pub fn spanish_id_letter(number: u32) -> char {
+    SPANISH_ID_LETTERS[(number % 23) as usize]
 }
 
 /// Luhn Algorithm
@@ -103,3 +119,72 @@ pub fn validate_luhn(number: &str) -> bool {
     
     sum.is_multiple_of(10)
 }
+
+/// Luhn check digit that makes `payload` followed by the digit valid
+pub fn luhn_check_digit(payload: &str) -> u32 {
+    let sum: u32 = payload
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .rev()
+        .enumerate()
+        .map(|(i, d)| {
+            // Positions shift by one once the check digit is appended
+            if i % 2 == 0 {
+                let doubled = d * 2;
+                if doubled > 9 { doubled - 9 } else { doubled }
+            } else {
+                d
+            }
+        })
+        .sum();
+    
+    (10 - sum % 10) % 10
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iban_accepts_valid_and_rejects_altered() {
+        assert!(validate_iban("ES91 2100 0418 4502 0005 1332"));
+        assert!(validate_iban("GB82WEST12345698765432"));
+        assert!(!validate_iban("ES91 2100 0418 4502 0005 1333"));
+        assert!(!validate_iban("ES9"));
+    }
+
+    #[test]
+    fn iban_ignores_non_ascii_without_panicking() {
+        // Multi-byte characters used to be kept and then sliced mid-char
+        assert!(!validate_iban("Aéxé1234567"));
+        assert!(!validate_iban("ééééééé"));
+        assert!(validate_iban("ES91é2100 0418 4502 0005 1332"));
+    }
+
+    #[test]
+    fn iban_check_digits_round_trip() {
+        let digits = iban_check_digits("ES", "21000418450200051332");
+        assert_eq!(digits, "91");
+        assert!(validate_iban(&format!("ES{}21000418450200051332", digits)));
+    }
+
+    #[test]
+    fn luhn_check_digit_completes_valid_numbers() {
+        assert!(validate_luhn("4539 1488 0343 6467"));
+        assert!(!validate_luhn("4539 1488 0343 6468"));
+        assert!(!validate_luhn(""));
+        for payload in ["453914880343646", "7992739871", "0", "37828224631000"] {
+            let digit = luhn_check_digit(payload);
+            assert!(validate_luhn(&format!("{}{}", payload, digit)), "{}", payload);
+        }
+    }
+
+    #[test]
+    fn spanish_id_letters() {
+        assert!(validate_spanish_id("12345678Z"));
+        assert!(validate_spanish_id("X1234567L"));
+        assert!(!validate_spanish_id("12345678A"));
+        assert!(!validate_spanish_id("1234567Z"));
+        assert_eq!(spanish_id_letter(12345678), 'Z');
+    }
+}
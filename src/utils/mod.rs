@@ -1,3 +1,4 @@
 // src/utils/mod.rs
 
 pub mod checksum;
+pub mod text;
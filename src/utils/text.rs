@@ -0,0 +1,50 @@
+// src/utils/text.rs
+
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+use crate::normalizer::OffsetMap;
+
+/// Lower-case `text` and strip its accents, for case- and
+/// accent-insensitive matching (`Martínez` and `MARTINEZ` both fold to
+/// `martinez`).
+///
+/// The returned map locates every byte of the folded text in `text`.
+pub fn fold(text: &str) -> (String, OffsetMap) {
+    let mut folded = String::with_capacity(text.len());
+    let mut offsets = OffsetMap::default();
+
+    for (i, c) in text.char_indices() {
+        if c.is_ascii() {
+            offsets.push(folded.len(), 1, i, 1, true);
+            folded.push(c.to_ascii_lowercase());
+            continue;
+        }
+
+        let start = folded.len();
+        for base in c.nfd().filter(|d| !is_combining_mark(*d)) {
+            folded.extend(base.to_lowercase());
+        }
+        let folded_len = folded.len() - start;
+        if folded_len > 0 {
+            // A single character of the same width maps byte for byte
+            let mut chars = folded[start..].chars();
+            let one_to_one = chars.next().map(char::len_utf8) == Some(c.len_utf8()) && chars.next().is_none();
+            offsets.push(start, folded_len, i, c.len_utf8(), one_to_one);
+        }
+    }
+
+    (folded, offsets)
+}
+
+/// Fold a term the same way as the text, collapsing its whitespace
+pub fn fold_term(term: &str) -> String {
+    let collapsed = term.split_whitespace().collect::<Vec<_>>().join(" ");
+    fold(&collapsed).0
+}
+
+/// Whether `span` of `text` starts and ends on word boundaries
+pub fn is_whole_word(text: &str, start: usize, end: usize) -> bool {
+    let before = text[..start].chars().next_back();
+    let after = text[end..].chars().next();
+    !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+}
@@ -0,0 +1,252 @@
+// src/value_lists.rs
+
+use aho_corasick::{AhoCorasick, MatchKind};
+use std::collections::{BTreeMap, HashSet};
+use crate::{AnonymizeError, Result};
+use crate::config::{ValueListsConfig, ValueListEntries};
+use crate::detector::{CandidateMatch, Category, Confidence, Span};
+use crate::normalizer::OffsetMap;
+use crate::utils::text::fold;
+
+/// Detector id of the matches found by searching the text for
+/// denylisted values
+pub const DENYLIST_DETECTOR_ID: &str = "denylist";
+
+/// Priority of denylisted values found in the text, as high as the
+/// checksum-validated detectors
+const DENYLIST_PRIORITY: u32 = 100;
+
+/// How the lists treat a detected value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListVerdict {
+    /// Never anonymized, e.g. the company's own switchboard number
+    Allowed,
+    /// Always anonymized, whatever its score or context
+    Denied,
+    Unlisted,
+}
+
+#[derive(Default)]
+struct Entries {
+    allow: HashSet<String>,
+    deny: HashSet<String>,
+}
+
+impl Entries {
+    fn new(entries: &ValueListEntries) -> Self {
+        Self {
+            allow: entries.allow.iter().map(|v| comparison_key(v)).collect(),
+            deny: entries.deny.iter().map(|v| comparison_key(v)).collect(),
+        }
+    }
+}
+
+/// Denylisted values to search the text for
+struct DenylistSearch {
+    /// Comparison keys of the denylisted values
+    automaton: AhoCorasick,
+    /// Category of each key
+    categories: Vec<Category>,
+}
+
+/// Allowlists and denylists of values, per category and per detector.
+///
+/// Entries are compared with the normalized value of each match ignoring
+/// case, accents, spaces and punctuation, so `+34 912 345 678` in a list
+/// matches `+34-912-345-678` in a document. A value on both an allowlist
+/// and a denylist is anonymized. Denylisted values are also searched for
+/// in the text (`find_denied`), so they are anonymized even when no
+/// detector recognizes them.
+#[derive(Default)]
+pub struct ValueLists {
+    /// Lists by category placeholder name
+    categories: BTreeMap<String, Entries>,
+    /// Lists by detector id
+    detectors: BTreeMap<String, Entries>,
+    search: Option<DenylistSearch>,
+}
+
+impl ValueLists {
+    /// Build the lists described by `[lists]`. `detectors` gives the id and
+    /// category of every configured detector: detector lists take that
+    /// category, and lists of other ids are rejected.
+    pub fn from_config(config: &ValueListsConfig, detectors: &[(String, Category)]) -> Result<Self> {
+        let mut denied: Vec<(String, Category)> = Vec::new();
+        for (name, entries) in &config.categories {
+            let category = Category::from_placeholder_name(name);
+            denied.extend(entries.deny.iter().map(|v| (comparison_key(v), category.clone())));
+        }
+        for (id, entries) in &config.detectors {
+            let Some((_, category)) = detectors.iter().find(|(known, _)| known == id) else {
+                let ids: Vec<&str> = detectors.iter().map(|(id, _)| id.as_str()).collect();
+                return Err(AnonymizeError::ConfigError {
+                    message: format!("[lists.detectors.{}]: unknown detector (available: {})", id, ids.join(", ")),
+                });
+            };
+            denied.extend(entries.deny.iter().map(|v| (comparison_key(v), category.clone())));
+        }
+
+        Ok(Self {
+            categories: config.categories
+                .iter()
+                .map(|(category, entries)| (category.to_uppercase(), Entries::new(entries)))
+                .collect(),
+            detectors: config.detectors
+                .iter()
+                .map(|(id, entries)| (id.clone(), Entries::new(entries)))
+                .collect(),
+            search: DenylistSearch::new(denied)?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.categories.is_empty() && self.detectors.is_empty()
+    }
+
+    /// Whether any value is denylisted
+    pub fn has_denylist(&self) -> bool {
+        self.search.is_some()
+    }
+
+    /// Every occurrence of a denylisted value in `text`, as a match of the
+    /// list's category from the `denylist` detector. Occurrences are
+    /// compared like list entries (ignoring case, accents, spaces and
+    /// punctuation) and must not be part of a longer word or number.
+    pub fn find_denied(&self, text: &str) -> Vec<CandidateMatch> {
+        let Some(search) = &self.search else {
+            return Vec::new();
+        };
+        let (folded, offsets) = fold(text);
+
+        // Letters and digits of the folded text, mapped back to it
+        let mut compact = String::with_capacity(folded.len());
+        let mut origin = OffsetMap::default();
+        for (i, c) in folded.char_indices().filter(|(_, c)| c.is_alphanumeric()) {
+            origin.push(compact.len(), c.len_utf8(), i, c.len_utf8(), true);
+            compact.push(c);
+        }
+
+        search.automaton
+            .find_iter(&compact)
+            .filter_map(|m| {
+                let Span { start, end } = origin.to_original(Span { start: m.start(), end: m.end() });
+                let before = folded[..start].chars().next_back();
+                let after = folded[end..].chars().next();
+                if before.is_some_and(char::is_alphanumeric) || after.is_some_and(char::is_alphanumeric) {
+                    return None;
+                }
+
+                let span = offsets.to_original(Span { start, end });
+                Some(CandidateMatch {
+                    span,
+                    detector_id: DENYLIST_DETECTOR_ID.to_string(),
+                    category: search.categories[m.pattern().as_usize()].clone(),
+                    priority: DENYLIST_PRIORITY,
+                    confidence: Confidence::PatternOnly,
+                    score: 1.0,
+                    raw_value: text[span.start..span.end].to_string(),
+                    normalized_value: None,
+                })
+            })
+            .collect()
+    }
+
+    pub fn verdict(&self, m: &CandidateMatch) -> ListVerdict {
+        if self.is_empty() {
+            return ListVerdict::Unlisted;
+        }
+
+        let key = comparison_key(m.entity_value());
+        let lists = [
+            self.categories.get(&m.category.placeholder_name()),
+            self.detectors.get(&m.detector_id),
+        ];
+        let lists = lists.iter().flatten();
+
+        if lists.clone().any(|e| e.deny.contains(&key)) {
+            ListVerdict::Denied
+        } else if lists.clone().any(|e| e.allow.contains(&key)) {
+            ListVerdict::Allowed
+        } else {
+            ListVerdict::Unlisted
+        }
+    }
+}
+
+impl DenylistSearch {
+    /// `None` when nothing is denylisted
+    fn new(denied: Vec<(String, Category)>) -> Result<Option<Self>> {
+        let mut keys = Vec::new();
+        let mut categories = Vec::new();
+        let mut seen = HashSet::new();
+        for (key, category) in denied {
+            // The first list naming a value gives its category
+            if !key.is_empty() && seen.insert(key.clone()) {
+                keys.push(key);
+                categories.push(category);
+            }
+        }
+        if keys.is_empty() {
+            return Ok(None);
+        }
+
+        let automaton = AhoCorasick::builder()
+            .match_kind(MatchKind::LeftmostLongest)
+            .build(&keys)
+            .map_err(|e| AnonymizeError::ConfigError {
+                message: format!("Denylists: {}", e),
+            })?;
+        Ok(Some(Self { automaton, categories }))
+    }
+}
+
+/// Folded letters and digits of a value
+fn comparison_key(value: &str) -> String {
+    fold(value).0.chars().filter(|c| c.is_alphanumeric()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lists(toml: &str) -> Result<ValueLists> {
+        let config: ValueListsConfig = toml::from_str(toml).unwrap();
+        ValueLists::from_config(&config, &[("work_order".to_string(), Category::WorkOrder)])
+    }
+
+    #[test]
+    fn denylisted_values_are_found_in_text() {
+        let lists = lists("[categories.PROJECT_CODE]\ndeny = [\"Zeta-42\"]\n[detectors.work_order]\ndeny = [\"WO 000001\"]").unwrap();
+        let found = lists.find_denied("Ref ZETA 42 and wo-000001; not XZETA42 nor Zeta-421");
+        let values: Vec<(&str, Category)> = found.iter().map(|m| (m.raw_value.as_str(), m.category.clone())).collect();
+        assert_eq!(values, [("ZETA 42", Category::ProjectCode), ("wo-000001", Category::WorkOrder)]);
+        assert!(found.iter().all(|m| m.detector_id == DENYLIST_DETECTOR_ID && m.score == 1.0));
+    }
+
+    #[test]
+    fn spans_cover_accented_text() {
+        let lists = lists("[categories.CLIENT_NAME]\ndeny = [\"Ibérica\"]").unwrap();
+        let text = "Cliente: IBÉRICA.";
+        let found = lists.find_denied(text);
+        assert_eq!(found.len(), 1);
+        assert_eq!(&text[found[0].span.start..found[0].span.end], "IBÉRICA");
+    }
+
+    #[test]
+    fn spans_skip_punctuation_between_multibyte_letters() {
+        let lists = lists("[categories.CLIENT_NAME]\ndeny = [\"Müller GmbH\"]").unwrap();
+        let text = "Pedido de ÑU, MÜLLER  G.m.b.H. y más";
+        let found = lists.find_denied(text);
+        assert_eq!(found.len(), 1);
+        assert_eq!(&text[found[0].span.start..found[0].span.end], "MÜLLER  G.m.b.H");
+    }
+
+    #[test]
+    fn unknown_detector_lists_are_rejected() {
+        assert!(matches!(
+            lists("[detectors.work_ordr]\ndeny = [\"WO-1\"]"),
+            Err(AnonymizeError::ConfigError { .. })
+        ));
+        assert!(!lists("[categories.PHONE]\nallow = [\"912\"]").unwrap().has_denylist());
+    }
+}
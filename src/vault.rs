@@ -0,0 +1,343 @@
+// src/vault.rs
+
+use aho_corasick::{AhoCorasick, MatchKind};
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, rand_core::RngCore},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use serde::{Serialize, Deserialize};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+use zeroize::{Zeroize, Zeroizing};
+use crate::{AnonymizeError, Result};
+
+/// Format version of `SealedVault`
+const VAULT_FORMAT_VERSION: u32 = 1;
+
+/// Cipher of sealed vaults
+const VAULT_ALGORITHM: &str = "ChaCha20-Poly1305";
+
+/// Key derivation of sealed vaults
+const VAULT_KDF: &str = "PBKDF2-HMAC-SHA256";
+
+/// PBKDF2-HMAC-SHA256 iterations used when sealing a vault
+const KDF_ITERATIONS: u32 = 600_000;
+
+/// Iteration counts `open` accepts. The count is read from the vault, so
+/// vaults sealed before the default was raised stay readable; vaults come
+/// back from clients, so the count is bounded to keep a request from
+/// holding a CPU core for as long as it likes.
+const ACCEPTED_ITERATIONS: RangeInclusive<u32> = 100_000..=10_000_000;
+
+const SALT_LEN: usize = 16;
+
+/// Placeholder → original value mappings, kept in memory in clear text.
+///
+/// Originals are wiped from memory when the vault is dropped. Use `seal`
+/// before writing a vault anywhere.
+#[derive(Default)]
+pub struct MappingVault {
+    entries: BTreeMap<String, String>,
+}
+
+impl MappingVault {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the original value behind a placeholder. A placeholder
+    /// already standing for another value is an error: deanonymization
+    /// would restore the wrong one.
+    pub fn insert(&mut self, placeholder: String, mut original: String) -> Result<()> {
+        match self.entries.get(&placeholder) {
+            Some(previous) if *previous != original => {
+                original.zeroize();
+                Err(AnonymizeError::VaultError {
+                    message: format!("Placeholder '{}' stands for two different values", placeholder),
+                })
+            }
+            Some(_) => {
+                original.zeroize();
+                Ok(())
+            }
+            None => {
+                self.entries.insert(placeholder, original);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn get(&self, placeholder: &str) -> Option<&str> {
+        self.entries.get(placeholder).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Add all mappings from another vault, e.g. one per processed document
+    pub fn merge(&mut self, mut other: MappingVault) -> Result<()> {
+        for (placeholder, original) in std::mem::take(&mut other.entries) {
+            self.insert(placeholder, original)?;
+        }
+        Ok(())
+    }
+
+    /// Encrypt the mappings under a key derived from `passphrase`. Key
+    /// derivation is deliberately slow: run it off async executors.
+    pub fn seal(&self, passphrase: &str) -> Result<SealedVault> {
+        self.seal_with_iterations(passphrase, KDF_ITERATIONS)
+    }
+
+    fn seal_with_iterations(&self, passphrase: &str, iterations: u32) -> Result<SealedVault> {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let cipher = vault_cipher(passphrase, &salt, iterations);
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+        let plaintext = Zeroizing::new(serde_json::to_vec(&self.entries)
+            .map_err(|e| AnonymizeError::VaultError {
+                message: format!("Cannot serialize vault: {}", e),
+            })?);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_slice())
+            .map_err(|_| AnonymizeError::VaultError {
+                message: "Encryption failed".to_string(),
+            })?;
+
+        Ok(SealedVault {
+            version: VAULT_FORMAT_VERSION,
+            algorithm: VAULT_ALGORITHM.to_string(),
+            kdf: VAULT_KDF.to_string(),
+            iterations,
+            salt: BASE64.encode(salt),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        })
+    }
+}
+
+impl Drop for MappingVault {
+    fn drop(&mut self) {
+        for original in self.entries.values_mut() {
+            original.zeroize();
+        }
+    }
+}
+
+/// Encrypted, serializable form of a `MappingVault`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SealedVault {
+    pub version: u32,
+    pub algorithm: String,
+    pub kdf: String,
+    pub iterations: u32,
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+impl SealedVault {
+    /// Decrypt the vault. Fails if the passphrase is wrong, the vault was
+    /// modified or it was not sealed with this crate's algorithms and an
+    /// accepted iteration count. Key derivation is deliberately slow: run
+    /// it off async executors.
+    pub fn open(&self, passphrase: &str) -> Result<MappingVault> {
+        self.open_within(passphrase, ACCEPTED_ITERATIONS)
+    }
+
+    fn open_within(&self, passphrase: &str, accepted_iterations: RangeInclusive<u32>) -> Result<MappingVault> {
+        if self.version != VAULT_FORMAT_VERSION {
+            return Err(AnonymizeError::VaultError {
+                message: format!("Unsupported vault version {}", self.version),
+            });
+        }
+        if self.algorithm != VAULT_ALGORITHM {
+            return Err(AnonymizeError::VaultError {
+                message: format!("Unsupported vault algorithm '{}'", self.algorithm),
+            });
+        }
+        if self.kdf != VAULT_KDF {
+            return Err(AnonymizeError::VaultError {
+                message: format!("Unsupported key derivation '{}'", self.kdf),
+            });
+        }
+        if !accepted_iterations.contains(&self.iterations) {
+            return Err(AnonymizeError::VaultError {
+                message: format!(
+                    "Unsupported key derivation iteration count {} (accepted {} to {})",
+                    self.iterations, accepted_iterations.start(), accepted_iterations.end()
+                ),
+            });
+        }
+
+        let salt = decode_field("salt", &self.salt)?;
+        let nonce = decode_field("nonce", &self.nonce)?;
+        let ciphertext = decode_field("ciphertext", &self.ciphertext)?;
+        if nonce.len() != 12 {
+            return Err(AnonymizeError::VaultError {
+                message: "Invalid nonce length".to_string(),
+            });
+        }
+
+        let cipher = vault_cipher(passphrase, &salt, self.iterations);
+        let plaintext = Zeroizing::new(cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| AnonymizeError::VaultError {
+                message: "Wrong key or corrupted vault".to_string(),
+            })?);
+
+        let entries = serde_json::from_slice(&plaintext)
+            .map_err(|e| AnonymizeError::VaultError {
+                message: format!("Malformed vault contents: {}", e),
+            })?;
+        Ok(MappingVault { entries })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("BUG: sealed vault is always serializable")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| AnonymizeError::VaultError {
+            message: format!("Invalid vault file: {}", e),
+        })
+    }
+}
+
+/// Restore original values in a text anonymized with the vault's mappings.
+///
+/// Placeholders without an entry in the vault are left untouched.
+pub fn deanonymize(text: &str, vault: &MappingVault) -> String {
+    if vault.is_empty() {
+        return text.to_string();
+    }
+
+    let placeholders: Vec<&str> = vault.entries.keys().map(String::as_str).collect();
+    let automaton = AhoCorasick::builder()
+        .match_kind(MatchKind::LeftmostLongest)
+        .build(&placeholders)
+        .expect("BUG: vault placeholders always build an automaton");
+
+    let mut restored = String::with_capacity(text.len());
+    automaton.replace_all_with(text, &mut restored, |m, _, dst| {
+        dst.push_str(&vault.entries[placeholders[m.pattern().as_usize()]]);
+        true
+    });
+    restored
+}
+
+fn vault_cipher(passphrase: &str, salt: &[u8], iterations: u32) -> ChaCha20Poly1305 {
+    let mut key = Zeroizing::new([0u8; 32]);
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, iterations, key.as_mut());
+    ChaCha20Poly1305::new(Key::from_slice(key.as_ref()))
+}
+
+fn decode_field(name: &str, value: &str) -> Result<Vec<u8>> {
+    BASE64.decode(value).map_err(|e| AnonymizeError::VaultError {
+        message: format!("Invalid base64 in vault field '{}': {}", name, e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> MappingVault {
+        let mut vault = MappingVault::new();
+        vault.insert("[EMAIL_001]".to_string(), "john@example.com".to_string()).unwrap();
+        vault.insert("[EMAIL_0011]".to_string(), "ann@example.com".to_string()).unwrap();
+        vault
+    }
+
+    #[test]
+    fn placeholders_stand_for_one_value() {
+        let mut vault = sample();
+        vault.insert("[EMAIL_001]".to_string(), "john@example.com".to_string()).unwrap();
+        assert!(matches!(
+            vault.insert("[EMAIL_001]".to_string(), "mary@example.com".to_string()),
+            Err(AnonymizeError::VaultError { .. })
+        ));
+        assert_eq!(vault.get("[EMAIL_001]"), Some("john@example.com"));
+        assert_eq!(vault.len(), 2);
+
+        let mut other = MappingVault::new();
+        other.insert("[EMAIL_0011]".to_string(), "bob@example.com".to_string()).unwrap();
+        assert!(vault.merge(other).is_err());
+    }
+
+    /// Iterations of test vaults, cheap enough for unoptimized builds
+    const CHEAP_ITERATIONS: RangeInclusive<u32> = 1_000..=4_000;
+
+    fn cheap_seal(vault: &MappingVault, passphrase: &str, iterations: u32) -> SealedVault {
+        vault.seal_with_iterations(passphrase, iterations).unwrap()
+    }
+
+    fn cheap_open(sealed: &SealedVault, passphrase: &str) -> Result<MappingVault> {
+        sealed.open_within(passphrase, CHEAP_ITERATIONS)
+    }
+
+    #[test]
+    fn seal_open_round_trip_and_rejections() {
+        let sealed = SealedVault::from_json(&cheap_seal(&sample(), "secret", 1_000).to_json()).unwrap();
+        let vault = cheap_open(&sealed, "secret").unwrap();
+        assert_eq!(vault.get("[EMAIL_001]"), Some("john@example.com"));
+        assert_eq!(deanonymize("[EMAIL_0011] / [EMAIL_001] / [EMAIL_002]", &vault),
+            "ann@example.com / john@example.com / [EMAIL_002]");
+
+        assert!(cheap_open(&sealed, "wrong").is_err());
+
+        let mut tampered = sealed.clone();
+        tampered.ciphertext = BASE64.encode(b"not the ciphertext");
+        assert!(cheap_open(&tampered, "secret").is_err());
+    }
+
+    #[test]
+    fn iteration_count_is_read_from_the_vault() {
+        assert!(ACCEPTED_ITERATIONS.contains(&KDF_ITERATIONS));
+        assert_eq!(sample().seal_with_iterations("secret", 1).unwrap().iterations, 1);
+        // A vault sealed with another count in the accepted range opens
+        for iterations in [1_000, 4_000] {
+            let sealed = cheap_seal(&sample(), "secret", iterations);
+            assert_eq!(sealed.iterations, iterations);
+            assert_eq!(cheap_open(&sealed, "secret").unwrap().get("[EMAIL_001]"), Some("john@example.com"));
+        }
+        // Same key material, different count: not the same key
+        let mut recounted = cheap_seal(&sample(), "secret", 1_000);
+        recounted.iterations = 2_000;
+        assert!(cheap_open(&recounted, "secret").is_err());
+    }
+
+    #[test]
+    fn open_rejects_foreign_parameters_before_deriving() {
+        let sealed = SealedVault {
+            version: VAULT_FORMAT_VERSION,
+            algorithm: VAULT_ALGORITHM.to_string(),
+            kdf: VAULT_KDF.to_string(),
+            iterations: KDF_ITERATIONS,
+            salt: BASE64.encode([0u8; SALT_LEN]),
+            nonce: BASE64.encode([0u8; 12]),
+            ciphertext: String::new(),
+        };
+        for vault in [
+            SealedVault { iterations: u32::MAX, ..sealed.clone() },
+            SealedVault { iterations: ACCEPTED_ITERATIONS.end() + 1, ..sealed.clone() },
+            SealedVault { iterations: ACCEPTED_ITERATIONS.start() - 1, ..sealed.clone() },
+            SealedVault { iterations: 0, ..sealed.clone() },
+            SealedVault { algorithm: "AES-256-GCM".to_string(), ..sealed.clone() },
+            SealedVault { kdf: "scrypt".to_string(), ..sealed.clone() },
+            SealedVault { version: 2, ..sealed.clone() },
+        ] {
+            let Err(AnonymizeError::VaultError { message }) = vault.open("secret") else {
+                panic!("accepted {:?}", vault);
+            };
+            assert!(message.starts_with("Unsupported"), "{}", message);
+        }
+    }
+}
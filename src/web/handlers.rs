@@ -1,25 +1,43 @@
 // src/web/handlers.rs
 
 use axum::{
-    extract::{Json, Multipart, Form},
-    http::StatusCode,
+    extract::{Json, Multipart, Form, Path, Query, State},
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
 };
 use serde::{Deserialize, Serialize};
 use crate::{
-    Anonymizer, 
-    EmailDetector, PhoneDetector, SpanishIdDetector, IbanDetector,
-    CreditCardDetector, SsnDetector, ProjectCodeDetector, ContractNumberDetector,
-    WorkOrderDetector, PurchaseOrderDetector, SerialNumberDetector,
-    CostCenterDetector,
+    AnonymizationOutput,
+    AnonymizationSession,
     AuditReport,
+    ChainStatus,
+    ExportFormat,
+    Ledger,
+    LedgerEntry,
+    SealedVault,
+    MappingVault,
+    ScanReport,
+    deanonymize,
     document_processor,
+    records_csv,
+    statistics_csv,
+    report_html,
+    read_report_value,
+    REPORT_SCHEMA,
 };
+use super::AppState;
+use super::sessions::SharedSession;
 
 /// Request payload for the /anonymize endpoint
 #[derive(Deserialize)]
 pub struct AnonymizeRequest {
     pub text: String,
+    /// When present, the placeholder mappings are returned sealed under this key
+    #[serde(default)]
+    pub vault_key: Option<String>,
+    /// Share placeholders with other documents of this session
+    #[serde(default)]
+    pub session_id: Option<String>,
 }
 
 /// Response payload for the /anonymize endpoint
@@ -28,6 +46,8 @@ pub struct AnonymizeResponse {
     pub anonymized_text: String,
     pub audit_report: AuditReport,
     pub hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vault: Option<SealedVault>,
 }
 
 /// Response payload for the /anonymize-file endpoint
@@ -36,6 +56,71 @@ pub struct AnonymizeFileResponse {
     pub file_base64: String,
     pub filename: String,
     pub audit_report: AuditReport,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vault: Option<SealedVault>,
+}
+
+/// Request payload for the /scan endpoint
+#[derive(Deserialize)]
+pub struct ScanRequest {
+    pub text: String,
+    /// Also list the candidates discarded by conflict resolution
+    #[serde(default)]
+    pub include_discarded: bool,
+}
+
+/// Request payload for the /export endpoint: a saved /anonymize
+/// response, or just its report for the CSV formats
+#[derive(Deserialize)]
+pub struct ExportRequest {
+    /// Report of any format version, upgraded before rendering
+    pub audit_report: serde_json::Value,
+    /// Required by the HTML format
+    #[serde(default)]
+    pub anonymized_text: Option<String>,
+    pub format: ExportFormat,
+}
+
+/// Query of the /ledger endpoint
+#[derive(Deserialize)]
+pub struct LedgerQuery {
+    /// Only list the runs of the input with this SHA-256
+    #[serde(default)]
+    pub input_hash: Option<String>,
+}
+
+/// Request payload for the /deanonymize endpoint
+#[derive(Deserialize)]
+pub struct DeanonymizeRequest {
+    pub text: String,
+    pub vault: SealedVault,
+    pub vault_key: String,
+}
+
+/// Response payload for the /deanonymize endpoint
+#[derive(Serialize)]
+pub struct DeanonymizeResponse {
+    pub text: String,
+}
+
+/// Response payload describing an anonymization session
+#[derive(Serialize)]
+pub struct SessionResponse {
+    pub session_id: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub documents_processed: usize,
+    pub entity_count: usize,
+}
+
+impl From<&AnonymizationSession> for SessionResponse {
+    fn from(session: &AnonymizationSession) -> Self {
+        Self {
+            session_id: session.id().to_string(),
+            created_at: session.created_at(),
+            documents_processed: session.documents_processed(),
+            entity_count: session.entity_count(),
+        }
+    }
 }
 
 /// Custom error for HTTP responses
@@ -62,40 +147,20 @@ where
     }
 }
 
-/// Create anonymization engine with all detectors
-fn create_anonymizer() -> Anonymizer {
-    let mut engine = Anonymizer::new();
-    
-    // Personal data detectors
-    engine.add_detector(Box::new(EmailDetector::new()));
-    engine.add_detector(Box::new(PhoneDetector::new()));
-    engine.add_detector(Box::new(SpanishIdDetector::new()));
-    engine.add_detector(Box::new(IbanDetector::new()));
-    engine.add_detector(Box::new(CreditCardDetector::new()));
-    engine.add_detector(Box::new(SsnDetector::new()));
-    
-    // Corporate/Industrial detectors
-    engine.add_detector(Box::new(ProjectCodeDetector::new()));
-    engine.add_detector(Box::new(ContractNumberDetector::new()));
-    engine.add_detector(Box::new(WorkOrderDetector::new()));
-    engine.add_detector(Box::new(PurchaseOrderDetector::new()));
-    engine.add_detector(Box::new(SerialNumberDetector::new()));
-    engine.add_detector(Box::new(CostCenterDetector::new()));
-    
-    engine
-}
-
 /// Handler for plain text anonymization
 pub async fn anonymize_handler(
+    State(state): State<AppState>,
     Form(payload): Form<AnonymizeRequest>,
 ) -> Result<Json<AnonymizeResponse>, AppError> {
-    let engine = create_anonymizer();
-    let output = engine.anonymize(&payload.text)?;
+    let output = anonymize_text(&state, payload.text, payload.session_id.as_deref()).await?;
+    state.metrics.record(&output.report.statistics);
+    let vault = seal_vault(output.vault, payload.vault_key).await?;
 
     let response = AnonymizeResponse {
         anonymized_text: output.text,
         audit_report: output.report,
         hash: output.hash.value,
+        vault,
     };
 
     Ok(Json(response))
@@ -103,22 +168,40 @@ pub async fn anonymize_handler(
 
 /// Handler for file anonymization (returns JSON with base64)
 pub async fn anonymize_file_handler(
+    State(state): State<AppState>,
     mut multipart: Multipart,
 ) -> Result<Json<AnonymizeFileResponse>, AppError> {
     // Extract file from multipart
     let mut file_bytes: Option<Vec<u8>> = None;
     let mut filename: Option<String> = None;
+    let mut vault_key: Option<String> = None;
+    let mut session_id: Option<String> = None;
     
-    while let Some(field) = multipart.next_field().await
+    while let Some(mut field) = multipart.next_field().await
         .map_err(|e| AppError(format!("Error reading multipart: {}", e)))? 
     {
         let field_name = field.name().unwrap_or("").to_string();
         
         if field_name == "file" {
             filename = field.file_name().map(|s| s.to_string());
-            file_bytes = Some(field.bytes().await
+            // Read in chunks to stop at the configured limit, before the
+            // request body limit (slightly larger) is reached
+            let mut bytes = Vec::new();
+            while let Some(chunk) = field.chunk().await
                 .map_err(|e| AppError(format!("Error reading bytes: {}", e)))?
-                .to_vec());
+            {
+                if bytes.len() + chunk.len() > state.max_file_size {
+                    return Err(file_too_large(&state));
+                }
+                bytes.extend_from_slice(&chunk);
+            }
+            file_bytes = Some(bytes);
+        } else if field_name == "vault_key" {
+            vault_key = Some(field.text().await
+                .map_err(|e| AppError(format!("Error reading vault key: {}", e)))?);
+        } else if field_name == "session_id" {
+            session_id = Some(field.text().await
+                .map_err(|e| AppError(format!("Error reading session id: {}", e)))?);
         }
     }
     
@@ -126,17 +209,19 @@ pub async fn anonymize_file_handler(
     let file_bytes = file_bytes.ok_or_else(|| AppError("No file received".to_string()))?;
     let filename = filename.ok_or_else(|| AppError("No filename received".to_string()))?;
     
-    // Validate file size (max 10MB)
-    if file_bytes.len() > 10 * 1024 * 1024 {
-        return Err(AppError("File too large (max 10MB)".to_string()));
-    }
-    
-    // Create anonymization engine
-    let engine = create_anonymizer();
-    
-    // Process document
-    let processed = document_processor::process_document(&file_bytes, &filename, &engine)?;
+    // Process document (inside the session when one was given)
+    let session = find_session(&state, session_id.as_deref())?;
+    let anonymizer = state.anonymizer.clone();
+    let processed = tokio::task::spawn_blocking(move || match session {
+        Some(session) => {
+            let mut session = session.blocking_lock();
+            document_processor::process_document(&file_bytes, &filename, &anonymizer, Some(&mut session))
+        }
+        None => document_processor::process_document(&file_bytes, &filename, &anonymizer, None),
+    }).await??;
     
+    state.metrics.record(&processed.audit_report.statistics);
+
     // IMPORTANT: Validate that processed content is not empty
     if processed.content.is_empty() {
         return Err(AppError("Processed document is empty".to_string()));
@@ -154,16 +239,190 @@ pub async fn anonymize_file_handler(
     eprintln!("✓ File processed: {} bytes -> {} base64 characters", 
               processed.content.len(), file_base64.len());
     
+    let vault = seal_vault(processed.vault, vault_key).await?;
+
     // Create response with full audit report
     let response = AnonymizeFileResponse {
         file_base64,
         filename: processed.filename,
         audit_report: processed.audit_report,
+        vault,
     };
     
     Ok(Json(response))
 }
 
+/// Handler listing the sensitive data in a text without anonymizing it
+pub async fn scan_handler(
+    State(state): State<AppState>,
+    Form(payload): Form<ScanRequest>,
+) -> Result<Json<ScanReport>, AppError> {
+    let anonymizer = state.anonymizer.clone();
+    let report = tokio::task::spawn_blocking(move || {
+        anonymizer.scan(&payload.text, payload.include_discarded)
+    }).await??;
+    state.metrics.record_scan(&report);
+    Ok(Json(report))
+}
+
+/// Handler rendering an audit report as a CSV or HTML download
+pub async fn export_handler(
+    Json(payload): Json<ExportRequest>,
+) -> Result<Response, AppError> {
+    let report = read_report_value(payload.audit_report)?;
+    let body = match payload.format {
+        ExportFormat::Csv => records_csv(&report),
+        ExportFormat::StatisticsCsv => statistics_csv(&report),
+        ExportFormat::Html => {
+            let text = payload.anonymized_text.as_deref()
+                .ok_or_else(|| AppError("The HTML export needs the anonymized_text".to_string()))?;
+            report_html(&report, text)
+        }
+    };
+
+    let disposition = format!("attachment; filename=\"{}\"", payload.format.file_name());
+    Ok((
+        [
+            (header::CONTENT_TYPE, payload.format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    ).into_response())
+}
+
+/// Handler exposing server metrics in the Prometheus text format
+pub async fn metrics_handler(
+    State(state): State<AppState>,
+) -> Response {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        state.metrics.render(),
+    ).into_response()
+}
+
+/// Handler publishing the JSON Schema of audit reports
+pub async fn report_schema_handler() -> Response {
+    ([(header::CONTENT_TYPE, "application/schema+json")], REPORT_SCHEMA).into_response()
+}
+
+/// Handler listing the runs recorded in the ledger
+pub async fn ledger_handler(
+    State(state): State<AppState>,
+    Query(query): Query<LedgerQuery>,
+) -> Result<Json<Vec<LedgerEntry>>, AppError> {
+    let entries = with_ledger(&state, move |ledger| match query.input_hash.as_deref() {
+        Some(hash) => ledger.find_by_input_hash(hash),
+        None => ledger.entries(),
+    }).await?;
+    Ok(Json(entries))
+}
+
+/// Handler checking the ledger's hash chain
+pub async fn verify_ledger_handler(
+    State(state): State<AppState>,
+) -> Result<Json<ChainStatus>, AppError> {
+    Ok(Json(with_ledger(&state, Ledger::verify).await?))
+}
+
+/// Handler restoring original values from a sealed vault
+pub async fn deanonymize_handler(
+    Json(payload): Json<DeanonymizeRequest>,
+) -> Result<Json<DeanonymizeResponse>, AppError> {
+    // Key derivation takes a while: keep it off the async workers
+    let text = tokio::task::spawn_blocking(move || {
+        payload.vault.open(&payload.vault_key)
+            .map(|vault| deanonymize(&payload.text, &vault))
+    }).await??;
+
+    Ok(Json(DeanonymizeResponse { text }))
+}
+
+/// Handler opening a new anonymization session
+pub async fn create_session_handler(
+    State(state): State<AppState>,
+) -> Result<Json<SessionResponse>, AppError> {
+    let session = AnonymizationSession::new();
+    let response = SessionResponse::from(&session);
+    state.sessions.insert(session)
+        .ok_or_else(|| AppError("Too many open sessions".to_string()))?;
+    Ok(Json(response))
+}
+
+/// Handler describing an open session
+pub async fn session_info_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<SessionResponse>, AppError> {
+    let session = find_session(&state, Some(&id))?
+        .ok_or_else(|| AppError(format!("Unknown session: {}", id)))?;
+    let response = SessionResponse::from(&*session.lock().await);
+    Ok(Json(response))
+}
+
+/// Handler closing a session and forgetting its placeholder table
+pub async fn close_session_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<SessionResponse>, AppError> {
+    let session = state.sessions.remove(&id)
+        .ok_or_else(|| AppError(format!("Unknown session: {}", id)))?;
+    let response = SessionResponse::from(&*session.lock().await);
+    Ok(Json(response))
+}
+
+/// Anonymize a text on a blocking thread, inside the given session when
+/// there is one
+async fn anonymize_text(
+    state: &AppState,
+    text: String,
+    session_id: Option<&str>,
+) -> Result<AnonymizationOutput, AppError> {
+    let session = find_session(state, session_id)?;
+    let anonymizer = state.anonymizer.clone();
+    let output = tokio::task::spawn_blocking(move || match session {
+        Some(session) => anonymizer.anonymize_in_session(&text, &mut session.blocking_lock()),
+        None => anonymizer.anonymize(&text),
+    }).await??;
+    Ok(output)
+}
+
+fn file_too_large(state: &AppState) -> AppError {
+    AppError(format!("File too large (max {} bytes)", state.max_file_size))
+}
+
+/// Session named by a request, `None` when no (or an empty) id was given
+fn find_session(state: &AppState, session_id: Option<&str>) -> Result<Option<SharedSession>, AppError> {
+    match session_id.filter(|id| !id.is_empty()) {
+        Some(id) => state.sessions.get(id)
+            .map(Some)
+            .ok_or_else(|| AppError(format!("Unknown session: {}", id))),
+        None => Ok(None),
+    }
+}
+
+/// Run an operation on the configured ledger, on a blocking thread as it
+/// reads the whole file
+async fn with_ledger<T, F>(state: &AppState, operation: F) -> Result<T, AppError>
+where
+    T: Send + 'static,
+    F: FnOnce(&Ledger) -> crate::Result<T> + Send + 'static,
+{
+    let anonymizer = state.anonymizer.clone();
+    tokio::task::spawn_blocking(move || match anonymizer.ledger() {
+        Some(ledger) => Ok(operation(ledger)?),
+        None => Err(AppError("No ledger configured ([ledger] path)".to_string())),
+    }).await?
+}
+
+/// Seal the mappings when the client supplied a (non-empty) vault key,
+/// on a blocking thread as key derivation takes a while
+async fn seal_vault(vault: MappingVault, key: Option<String>) -> Result<Option<SealedVault>, AppError> {
+    match key.filter(|key| !key.is_empty()) {
+        Some(key) => Ok(Some(tokio::task::spawn_blocking(move || vault.seal(&key)).await??)),
+        None => Ok(None),
+    }
+}
+
 /// Encode bytes to base64 string using base64 crate
 /// Guarantees no spaces or line breaks
 fn base64_encode(bytes: &[u8]) -> String {
@@ -0,0 +1,189 @@
+// src/web/metrics.rs
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use crate::{ScanReport, Statistics};
+use crate::audit_report::{DetectorStatistics, StageTimings};
+
+/// Totals accumulated over the runs and scans served, exposed in the
+/// Prometheus text format by `/metrics`
+#[derive(Default)]
+pub struct Metrics {
+    totals: Mutex<Totals>,
+}
+
+#[derive(Default)]
+struct Totals {
+    runs: u64,
+    scans: u64,
+    /// Microseconds by stage name
+    stages: BTreeMap<&'static str, u64>,
+    /// Occurrences replaced by category placeholder name
+    matches: BTreeMap<String, u64>,
+    /// Candidates, accepted matches and microseconds by detector id
+    detectors: BTreeMap<String, (u64, u64, u64)>,
+}
+
+impl Totals {
+    fn add_stages(&mut self, timings: &StageTimings, detectors: &BTreeMap<String, DetectorStatistics>) {
+        for (stage, micros) in timings.stages() {
+            *self.stages.entry(stage).or_default() += micros;
+        }
+        for (id, detector) in detectors {
+            let entry = self.detectors.entry(id.clone()).or_default();
+            entry.0 += detector.candidates as u64;
+            entry.1 += detector.accepted as u64;
+            entry.2 += detector.duration_us;
+        }
+    }
+}
+
+impl Metrics {
+    /// Add the statistics of one run
+    pub fn record(&self, statistics: &Statistics) {
+        let mut totals = self.totals.lock().expect("BUG: metrics lock poisoned");
+        totals.runs += 1;
+        for (category, count) in &statistics.matches_by_category {
+            *totals.matches.entry(category.clone()).or_default() += *count as u64;
+        }
+        totals.add_stages(&statistics.timings, &statistics.detectors);
+    }
+
+    /// Add the stage and detector statistics of one scan
+    pub fn record_scan(&self, scan: &ScanReport) {
+        let mut totals = self.totals.lock().expect("BUG: metrics lock poisoned");
+        totals.scans += 1;
+        totals.add_stages(&scan.timings, &scan.detectors);
+    }
+
+    /// All metrics in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let totals = self.totals.lock().expect("BUG: metrics lock poisoned");
+        let mut out = String::new();
+
+        header(&mut out, "anonymize_runs_total", "Anonymization runs completed");
+        let _ = writeln!(out, "anonymize_runs_total {}", totals.runs);
+
+        header(&mut out, "anonymize_scans_total", "Scans completed");
+        let _ = writeln!(out, "anonymize_scans_total {}", totals.scans);
+
+        header(&mut out, "anonymize_stage_seconds_total", "Time spent in each pipeline stage");
+        for (stage, micros) in &totals.stages {
+            let _ = writeln!(out, "anonymize_stage_seconds_total{{stage=\"{}\"}} {}", stage, seconds(*micros));
+        }
+
+        header(&mut out, "anonymize_matches_total", "Occurrences replaced, by category");
+        for (category, count) in &totals.matches {
+            let _ = writeln!(out, "anonymize_matches_total{{category=\"{}\"}} {}", label(category), count);
+        }
+
+        header(&mut out, "anonymize_detector_candidates_total", "Matches returned by each detector before filtering");
+        for (id, (candidates, _, _)) in &totals.detectors {
+            let _ = writeln!(out, "anonymize_detector_candidates_total{{detector=\"{}\"}} {}", label(id), candidates);
+        }
+
+        header(&mut out, "anonymize_detector_accepted_total", "Occurrences replaced, or found by scans, from each detector's matches");
+        for (id, (_, accepted, _)) in &totals.detectors {
+            let _ = writeln!(out, "anonymize_detector_accepted_total{{detector=\"{}\"}} {}", label(id), accepted);
+        }
+
+        header(&mut out, "anonymize_detector_seconds_total", "Time spent in each detector");
+        for (id, (_, _, micros)) in &totals.detectors {
+            let _ = writeln!(out, "anonymize_detector_seconds_total{{detector=\"{}\"}} {}", label(id), seconds(*micros));
+        }
+
+        out
+    }
+}
+
+fn header(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} counter", name);
+}
+
+fn seconds(micros: u64) -> f64 {
+    micros as f64 / 1_000_000.0
+}
+
+/// Escape a label value (backslash, double quote and line feed)
+fn label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Anonymizer, AnonymizeConfig};
+
+    fn anonymizer() -> Anonymizer {
+        Anonymizer::from_config(&AnonymizeConfig::default()).unwrap()
+    }
+
+    /// Metric lines of `render()` starting with `prefix`, comments excluded
+    fn lines<'a>(rendered: &'a str, prefix: &str) -> Vec<&'a str> {
+        rendered.lines().filter(|line| line.starts_with(prefix)).collect()
+    }
+
+    fn fixed(timings: &mut StageTimings, detectors: &mut BTreeMap<String, DetectorStatistics>) {
+        *timings = StageTimings { detection_us: 1_500_000, policy_us: 250, ..StageTimings::default() };
+        *detectors = BTreeMap::from([("email".to_string(), DetectorStatistics { candidates: 3, accepted: 2, duration_us: 500_000 })]);
+    }
+
+    #[test]
+    fn runs_and_scans_are_totalled() {
+        let anonymizer = anonymizer();
+        let metrics = Metrics::default();
+
+        let mut statistics = anonymizer.anonymize("john@example.com, ana@example.com").unwrap().report.statistics;
+        fixed(&mut statistics.timings, &mut statistics.detectors);
+        metrics.record(&statistics);
+        metrics.record(&statistics);
+        let mut scan = anonymizer.scan("john@example.com", false).unwrap();
+        fixed(&mut scan.timings, &mut scan.detectors);
+        metrics.record_scan(&scan);
+
+        let rendered = metrics.render();
+        assert_eq!(lines(&rendered, "anonymize_runs_total"), ["anonymize_runs_total 2"]);
+        assert_eq!(lines(&rendered, "anonymize_scans_total"), ["anonymize_scans_total 1"]);
+        assert_eq!(lines(&rendered, "anonymize_matches_total"), ["anonymize_matches_total{category=\"EMAIL\"} 4"]);
+        assert!(rendered.contains("anonymize_stage_seconds_total{stage=\"detection\"} 4.5\n"));
+        assert!(rendered.contains("anonymize_stage_seconds_total{stage=\"policy\"} 0.00075\n"));
+        assert_eq!(lines(&rendered, "anonymize_stage_seconds_total").len(), 6);
+        assert_eq!(lines(&rendered, "anonymize_detector_"), [
+            "anonymize_detector_candidates_total{detector=\"email\"} 9",
+            "anonymize_detector_accepted_total{detector=\"email\"} 6",
+            "anonymize_detector_seconds_total{detector=\"email\"} 1.5",
+        ]);
+    }
+
+    #[test]
+    fn every_metric_has_help_and_type() {
+        let rendered = Metrics::default().render();
+        for name in [
+            "anonymize_runs_total",
+            "anonymize_scans_total",
+            "anonymize_stage_seconds_total",
+            "anonymize_matches_total",
+            "anonymize_detector_candidates_total",
+            "anonymize_detector_accepted_total",
+            "anonymize_detector_seconds_total",
+        ] {
+            assert!(rendered.contains(&format!("# HELP {} ", name)), "{}", name);
+            assert!(rendered.contains(&format!("# TYPE {} counter\n", name)), "{}", name);
+        }
+        assert_eq!(lines(&rendered, "anonymize_runs_total"), ["anonymize_runs_total 0"]);
+    }
+
+    #[test]
+    fn label_values_are_escaped() {
+        assert_eq!(label("LOT\"A\\B\nC"), "LOT\\\"A\\\\B\\nC");
+
+        let anonymizer = anonymizer();
+        let metrics = Metrics::default();
+        let mut statistics = anonymizer.anonymize("john@example.com").unwrap().report.statistics;
+        statistics.matches_by_category = BTreeMap::from([("LOT\"A".to_string(), 1)]);
+        metrics.record(&statistics);
+        assert_eq!(lines(&metrics.render(), "anonymize_matches_total"), ["anonymize_matches_total{category=\"LOT\\\"A\"} 1"]);
+    }
+}
@@ -1,9 +1,12 @@
 // src/web/mod.rs
 
 pub mod handlers;
+pub mod metrics;
+pub mod sessions;
 
 use axum::{
-    routing::post,
+    extract::DefaultBodyLimit,
+    routing::{get, post},
     Router,
 };
 use tower_http::{
@@ -11,9 +14,48 @@ use tower_http::{
     services::ServeDir,
 };
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use crate::{Anonymizer, AnonymizeConfig};
+use metrics::Metrics;
+use sessions::SessionStore;
+
+/// State shared by all request handlers
+#[derive(Clone)]
+pub struct AppState {
+    pub anonymizer: Arc<Anonymizer>,
+    pub max_file_size: usize,
+    /// Open anonymization sessions
+    pub sessions: Arc<SessionStore>,
+    /// Timings and match counts accumulated over the runs served
+    pub metrics: Arc<Metrics>,
+}
+
+impl AppState {
+    /// Build the server state from a configuration
+    pub fn from_config(config: &AnonymizeConfig) -> crate::Result<Self> {
+        Ok(Self {
+            anonymizer: Arc::new(Anonymizer::from_config(config)?),
+            max_file_size: config.limits.max_file_size,
+            sessions: Arc::new(SessionStore::new(
+                config.limits.max_sessions,
+                Duration::from_secs(config.limits.session_idle_timeout),
+            )),
+            metrics: Arc::new(Metrics::default()),
+        })
+    }
+}
+
+/// Room for the multipart boundaries, headers and small form fields sent
+/// along with an uploaded file
+const MULTIPART_OVERHEAD: usize = 64 * 1024;
 
 /// Create the main application router
-pub fn create_router() -> Router {
+pub fn create_router(state: AppState) -> Router {
+    // Uploads may exceed axum's default 2 MB body limit up to the
+    // configured file size
+    let upload_limit = DefaultBodyLimit::max(state.max_file_size + MULTIPART_OVERHEAD);
+
     // CORS for development
     let cors = CorsLayer::new()
         .allow_origin(Any)
@@ -23,15 +65,28 @@ pub fn create_router() -> Router {
     Router::new()
         // API endpoints
         .route("/api/anonymize", post(handlers::anonymize_handler))
-        .route("/api/anonymize-file", post(handlers::anonymize_file_handler))
+        .route("/api/anonymize-file", post(handlers::anonymize_file_handler).layer(upload_limit))
+        .route("/api/scan", post(handlers::scan_handler))
+        .route("/api/export", post(handlers::export_handler))
+        .route("/api/schema/audit-report", get(handlers::report_schema_handler))
+        .route("/metrics", get(handlers::metrics_handler))
+        .route("/api/ledger", get(handlers::ledger_handler))
+        .route("/api/ledger/verify", get(handlers::verify_ledger_handler))
+        .route("/api/deanonymize", post(handlers::deanonymize_handler))
+        .route("/api/sessions", post(handlers::create_session_handler))
+        .route(
+            "/api/sessions/:id",
+            get(handlers::session_info_handler).delete(handlers::close_session_handler),
+        )
         // Serve static files
         .nest_service("/", ServeDir::new("src/web/static"))
         .layer(cors)
+        .with_state(state)
 }
 
 /// Start the web server
-pub async fn start_server(port: u16) -> Result<(), Box<dyn std::error::Error>> {
-    let app = create_router();
+pub async fn start_server(port: u16, config: AnonymizeConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let app = create_router(AppState::from_config(&config)?);
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
     
     println!("🚀 Server started at http://0.0.0.0:{}", port);
@@ -42,3 +97,56 @@ pub async fn start_server(port: u16) -> Result<(), Box<dyn std::error::Error>> {
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    fn router(max_file_size: usize) -> Router {
+        let mut config = AnonymizeConfig::default();
+        config.limits.max_file_size = max_file_size;
+        create_router(AppState::from_config(&config).unwrap())
+    }
+
+    /// Upload `size` bytes as `data.bin` and return the response status
+    /// and error message
+    async fn upload(router: Router, size: usize) -> (StatusCode, String) {
+        let boundary = "XBOUNDARYX";
+        let mut body = format!(
+            "--{b}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"data.bin\"\r\n\
+             Content-Type: application/octet-stream\r\n\r\n",
+            b = boundary
+        ).into_bytes();
+        body.extend(std::iter::repeat_n(b'a', size));
+        body.extend(format!("\r\n--{}--\r\n", boundary).into_bytes());
+
+        let request = Request::post("/api/anonymize-file")
+            .header("content-type", format!("multipart/form-data; boundary={}", boundary))
+            .body(Body::from(body))
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+        (status, json["error"].as_str().unwrap_or_default().to_string())
+    }
+
+    #[tokio::test]
+    async fn uploads_above_two_megabytes_reach_the_handler() {
+        // Past axum's default body limit, within the configured one: the
+        // file is read and only rejected for its type
+        let (_, error) = upload(router(10 * 1024 * 1024), 3 * 1024 * 1024).await;
+        assert_eq!(error, "Configuration error: Unsupported file type: data.bin");
+    }
+
+    #[tokio::test]
+    async fn uploads_above_the_limit_are_too_large() {
+        let (_, error) = upload(router(1024 * 1024), 1024 * 1024 + 1).await;
+        assert_eq!(error, "File too large (max 1048576 bytes)");
+        let (_, error) = upload(router(1024 * 1024), 1024 * 1024).await;
+        assert_eq!(error, "Configuration error: Unsupported file type: data.bin");
+    }
+}
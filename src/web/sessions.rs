@@ -0,0 +1,94 @@
+// src/web/sessions.rs
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::AnonymizationSession;
+
+/// A session shared between requests. Each session has its own lock, so
+/// documents of different sessions are processed concurrently.
+pub type SharedSession = Arc<tokio::sync::Mutex<AnonymizationSession>>;
+
+/// Open sessions of the server, bounded in number and closed once idle
+/// for too long
+pub struct SessionStore {
+    /// Session and time of its last use, by session id
+    sessions: Mutex<HashMap<String, (SharedSession, Instant)>>,
+    max_sessions: usize,
+    idle_timeout: Duration,
+}
+
+impl SessionStore {
+    pub fn new(max_sessions: usize, idle_timeout: Duration) -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+            max_sessions,
+            idle_timeout,
+        }
+    }
+
+    /// Register a new session; `None` when the maximum number of sessions
+    /// is already open
+    pub fn insert(&self, session: AnonymizationSession) -> Option<SharedSession> {
+        let mut sessions = self.lock();
+        self.evict_idle(&mut sessions);
+        if sessions.len() >= self.max_sessions {
+            return None;
+        }
+        let id = session.id().to_string();
+        let shared = Arc::new(tokio::sync::Mutex::new(session));
+        sessions.insert(id, (shared.clone(), Instant::now()));
+        Some(shared)
+    }
+
+    /// Session with this id, marking it as used
+    pub fn get(&self, id: &str) -> Option<SharedSession> {
+        let mut sessions = self.lock();
+        self.evict_idle(&mut sessions);
+        let (session, last_used) = sessions.get_mut(id)?;
+        *last_used = Instant::now();
+        Some(session.clone())
+    }
+
+    /// Close a session. Requests already using it finish normally.
+    pub fn remove(&self, id: &str) -> Option<SharedSession> {
+        self.lock().remove(id).map(|(session, _)| session)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, (SharedSession, Instant)>> {
+        self.sessions.lock().expect("BUG: session store poisoned")
+    }
+
+    fn evict_idle(&self, sessions: &mut HashMap<String, (SharedSession, Instant)>) {
+        sessions.retain(|_, (_, last_used)| last_used.elapsed() < self.idle_timeout);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn store_is_capped() {
+        let store = SessionStore::new(2, Duration::from_secs(60));
+        let first = AnonymizationSession::new();
+        let id = first.id().to_string();
+        assert!(store.insert(first).is_some());
+        assert!(store.insert(AnonymizationSession::new()).is_some());
+        assert!(store.insert(AnonymizationSession::new()).is_none());
+
+        assert!(store.remove(&id).is_some());
+        assert!(store.get(&id).is_none());
+        assert!(store.insert(AnonymizationSession::new()).is_some());
+    }
+
+    #[test]
+    fn idle_sessions_are_closed() {
+        let store = SessionStore::new(1, Duration::ZERO);
+        let session = AnonymizationSession::new();
+        let id = session.id().to_string();
+        assert!(store.insert(session).is_some());
+        assert!(store.get(&id).is_none());
+        assert!(store.insert(AnonymizationSession::new()).is_some());
+    }
+}